@@ -28,14 +28,17 @@ mod bsp;
 
 use embassy_executor::Spawner;
 use embedded_graphics::{
-    image::{Image, ImageRawLE},
     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
     pixelcolor::BinaryColor,
     prelude::*,
     text::{Baseline, Text},
 };
 
-use oled_async::{prelude::*, Builder};
+use oled_async::{
+    bitmap::{page_major, PageImage},
+    prelude::*,
+    Builder,
+};
 use {defmt_rtt as _, panic_probe as _};
 
 #[maybe_async_cfg::maybe(
@@ -53,24 +56,23 @@ async fn run() {
     type Display = oled_async::displays::sh1108::Sh1108_128_160;
     //type Display = oled_async::displays::ssd1309::Ssd1309_128_64;
 
-    let raw_disp = Builder::new(Display {})
+    let uninit = Builder::new(Display {})
         .with_rotation(crate::DisplayRotation::Rotate180)
         .connect(di);
 
-    let mut disp: GraphicsMode<_, _, { 128 * 160 / 8 }> = raw_disp.into();
-
-    disp.reset(&mut reset, &mut delay).unwrap();
-    disp.init().await.unwrap();
+    let mut disp: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
     disp.clear();
     disp.flush().await.unwrap();
 
-    let im: ImageRawLE<BinaryColor> = ImageRawLE::new(include_bytes!("./rust.raw"), 64);
+    // converted to the display's page layout at compile time
+    const LOGO_DATA: [u8; 512] = page_major(include_bytes!("./rust.raw"), 64, 64);
+    const LOGO: PageImage = PageImage::new(&LOGO_DATA, 64, 64);
 
     let (x_diff, y_diff) = {
         let dwidth = disp.get_dimensions().0 as i32;
         let dheight = disp.get_dimensions().1 as i32;
-        let iwidth = im.size().width as i32;
-        let iheight = im.size().height as i32;
+        let iwidth = LOGO.bitmap().size().0 as i32;
+        let iheight = LOGO.bitmap().size().1 as i32;
         (dwidth - iwidth, dheight - iheight)
     };
 
@@ -97,9 +99,7 @@ async fn run() {
         .draw(&mut disp)
         .unwrap();
 
-        Image::new(&im, Point::new(x, y_diff / 2))
-            .draw(&mut disp)
-            .unwrap();
+        disp.blit(x, y_diff / 2, &LOGO.bitmap());
         x += dir;
         if dir > 0 && x >= x_diff {
             dir = -1;
@@ -33,14 +33,11 @@ async fn main(_spawner: Spawner) {
     //type Display = oled_async::displays::sh1108::Sh1108_64_160;
     //type Display = oled_async::displays::ssd1309::Ssd1309_128_64;
 
-    let raw_disp = Builder::new(Display {})
+    let uninit = Builder::new(Display {})
         .with_rotation(crate::DisplayRotation::Rotate180)
         .connect(di);
 
-    let mut display: GraphicsMode<_, _> = raw_disp.into();
-
-    display.reset(&mut reset, &mut delay).unwrap();
-    display.init().await.unwrap();
+    let mut display: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
     display.clear();
     display.flush().await.unwrap();
 
@@ -46,14 +46,11 @@ async fn main(_spawner: Spawner) {
     //type Display = oled_async::displays::sh1108::Sh1108_64_160;
     //type Display = oled_async::displays::ssd1309::Ssd1309_128_64;
 
-    let raw_disp = Builder::new(Display {})
+    let uninit = Builder::new(Display {})
         .with_rotation(crate::DisplayRotation::Rotate180)
         .connect(di);
 
-    let mut disp: GraphicsMode<_, _, { 128 * 128 / 8 }> = raw_disp.into();
-
-    disp.reset(&mut reset, &mut delay).unwrap();
-    disp.init().await.unwrap();
+    let mut disp: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
     disp.clear();
     disp.flush().await.unwrap();
 
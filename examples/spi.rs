@@ -72,14 +72,11 @@ async fn main(_spawner: Spawner) {
 
     let mut delay = Delay {};
 
-    let raw_disp = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
+    let uninit = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
         .with_rotation(crate::DisplayRotation::Rotate180)
         .connect(di);
 
-    let mut disp: GraphicsMode<_, _> = raw_disp.into();
-
-    disp.reset(&mut reset, &mut delay).unwrap();
-    disp.init().await.unwrap();
+    let mut disp: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
     disp.clear();
     disp.flush().await.unwrap();
 
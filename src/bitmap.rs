@@ -0,0 +1,314 @@
+//! 1 bit per pixel bitmaps for fast copies into a display buffer
+//!
+//! [`GraphicsMode::blit`](crate::mode::GraphicsMode::blit) copies a [`Bitmap`] into the buffer a
+//! byte at a time, instead of setting each pixel through `DrawTarget::draw_iter`:
+//!
+//! ```rust,no_run
+//! use oled_async::bitmap::{Bitmap, BitmapLayout};
+//!
+//! const LOGO: Bitmap = Bitmap::new(include_bytes!("rust.raw"), 64, 64, BitmapLayout::RowMajor);
+//!
+//! display.blit(x, y, &LOGO);
+//! display.flush().await.unwrap();
+//! ```
+//!
+//! A [`PageImage`] stores the image in the page major layout of display RAM, so that a blit at
+//! a `y` that is a multiple of 8 copies it byte for byte. Images are converted at compile time
+//! with [`page_major`], or on the host from PBM files with `screenshot::Frame::to_page_major`
+//! (`std` feature).
+
+/// Memory layout of a 1 bit per pixel bitmap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitmapLayout {
+    /// Rows from the top, each padded to whole bytes, with the leftmost pixel in the most
+    /// significant bit. This is the layout of embedded-graphics `ImageRaw` and PBM images.
+    RowMajor,
+    /// Pages of 8 rows from the top, one byte per column with the top pixel in the least
+    /// significant bit. This is the layout of display RAM.
+    PageMajor,
+}
+
+/// A 1 bit per pixel bitmap borrowing its data, with set bits for lit pixels
+#[derive(Clone, Copy, Debug)]
+pub struct Bitmap<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    layout: BitmapLayout,
+}
+
+impl<'a> Bitmap<'a> {
+    /// Create a new bitmap of `width` x `height` pixels, stored in `data` in the given layout.
+    ///
+    /// Panics if `data` is too short for the size of the bitmap.
+    pub const fn new(data: &'a [u8], width: u32, height: u32, layout: BitmapLayout) -> Self {
+        let len = match layout {
+            BitmapLayout::RowMajor => width.div_ceil(8) as usize * height as usize,
+            BitmapLayout::PageMajor => width as usize * height.div_ceil(8) as usize,
+        };
+        assert!(data.len() >= len, "bitmap data is too short");
+
+        Bitmap {
+            data,
+            width,
+            height,
+            layout,
+        }
+    }
+
+    /// Get the size of the bitmap in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Get the memory layout of the bitmap
+    pub fn layout(&self) -> BitmapLayout {
+        self.layout
+    }
+
+    /// Get the bitmap data
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Get whether the pixel at `x`, `y` is lit. Pixels outside of the bitmap are dark.
+    pub fn pixel(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return false;
+        }
+        let (x, y) = (x as usize, y as usize);
+
+        match self.layout {
+            BitmapLayout::RowMajor => {
+                let stride = self.width.div_ceil(8) as usize;
+                self.data[y * stride + x / 8] & (0x80 >> (x % 8)) != 0
+            }
+            BitmapLayout::PageMajor => {
+                self.data[y / 8 * self.width as usize + x] & (1 << (y % 8)) != 0
+            }
+        }
+    }
+
+    /// Get the pixels at `x`, `y..y + 8` as a byte in display RAM layout, the top pixel in bit 0
+    pub(crate) fn column_bits(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || x as u32 >= self.width {
+            return 0;
+        }
+
+        match self.layout {
+            BitmapLayout::PageMajor => {
+                let (page, shift) = (y.div_euclid(8), y.rem_euclid(8));
+                let top = self.page_byte(x, page);
+                if shift == 0 {
+                    // aligned to a page, copied as is
+                    return top;
+                }
+                let bottom = self.page_byte(x, page + 1);
+
+                ((top as u16 | (bottom as u16) << 8) >> shift) as u8
+            }
+            BitmapLayout::RowMajor => {
+                (0..8).fold(0, |bits, i| bits | (self.pixel(x, y + i) as u8) << i)
+            }
+        }
+    }
+
+    /// Get the pixels at `x..x + 8`, `y` as a byte, the leftmost pixel in bit 0
+    pub(crate) fn row_bits(&self, x: i32, y: i32) -> u8 {
+        if y < 0 || y as u32 >= self.height {
+            return 0;
+        }
+
+        match self.layout {
+            BitmapLayout::RowMajor => {
+                let (byte, shift) = (x.div_euclid(8), x.rem_euclid(8));
+                let left = self.row_byte(byte, y) as u16;
+                let right = self.row_byte(byte + 1, y) as u16;
+                let bits = (((left << 8 | right) << shift) >> 8) as u8;
+
+                // leftmost pixel is the most significant bit
+                bits.reverse_bits()
+            }
+            BitmapLayout::PageMajor => {
+                (0..8).fold(0, |bits, i| bits | (self.pixel(x + i, y) as u8) << i)
+            }
+        }
+    }
+
+    /// Byte of column `x` in page `page`, `0` outside of the bitmap
+    fn page_byte(&self, x: i32, page: i32) -> u8 {
+        if page < 0 || page as u32 >= self.height.div_ceil(8) {
+            return 0;
+        }
+
+        self.data[page as usize * self.width as usize + x as usize]
+    }
+
+    /// Byte `byte` of row `y`, `0` outside of the bitmap
+    fn row_byte(&self, byte: i32, y: i32) -> u8 {
+        let stride = self.width.div_ceil(8);
+        if byte < 0 || byte as u32 >= stride {
+            return 0;
+        }
+
+        self.data[y as usize * stride as usize + byte as usize]
+    }
+}
+
+/// Convert a row major bitmap, such as a raw embedded-graphics image, to page major at compile
+/// time. `N` must be `width * height.div_ceil(8)`.
+///
+/// ```rust,no_run
+/// use oled_async::bitmap::{page_major, PageImage};
+///
+/// const LOGO_DATA: [u8; 512] = page_major(include_bytes!("rust.raw"), 64, 64);
+/// const LOGO: PageImage = PageImage::new(&LOGO_DATA, 64, 64);
+/// ```
+pub const fn page_major<const N: usize>(row_major: &[u8], width: u32, height: u32) -> [u8; N] {
+    assert!(
+        N == width as usize * height.div_ceil(8) as usize,
+        "N does not match the bitmap size"
+    );
+    let rows = Bitmap::new(row_major, width, height, BitmapLayout::RowMajor);
+    let stride = width.div_ceil(8) as usize;
+
+    let mut pages = [0; N];
+    let mut y = 0;
+    while y < height as usize {
+        let mut x = 0;
+        while x < width as usize {
+            if rows.data[y * stride + x / 8] & (0x80 >> (x % 8)) != 0 {
+                pages[y / 8 * width as usize + x] |= 1 << (y % 8);
+            }
+            x += 1;
+        }
+        y += 1;
+    }
+
+    pages
+}
+
+/// A 1 bit per pixel image stored in the native page major layout of the display
+///
+/// Draw it with [`GraphicsMode::blit`](crate::mode::GraphicsMode::blit) to copy it into the buffer
+/// a page at a time, or through embedded-graphics as an `ImageDrawable` on any draw target.
+#[derive(Clone, Copy, Debug)]
+pub struct PageImage<'a> {
+    bitmap: Bitmap<'a>,
+}
+
+impl<'a> PageImage<'a> {
+    /// Create a new image of `width` x `height` pixels from page major `data`, e.g. made by
+    /// [`page_major`] or `screenshot::Frame::to_page_major`.
+    ///
+    /// Panics if `data` is too short for the size of the image.
+    pub const fn new(data: &'a [u8], width: u32, height: u32) -> Self {
+        PageImage {
+            bitmap: Bitmap::new(data, width, height, BitmapLayout::PageMajor),
+        }
+    }
+
+    /// Get the image as a bitmap to blit
+    pub const fn bitmap(&self) -> Bitmap<'a> {
+        self.bitmap
+    }
+}
+
+impl<'a> From<PageImage<'a>> for Bitmap<'a> {
+    fn from(image: PageImage<'a>) -> Self {
+        image.bitmap
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    image::{GetPixel, ImageDrawable},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+};
+
+#[cfg(feature = "graphics")]
+impl OriginDimensions for PageImage<'_> {
+    fn size(&self) -> Size {
+        let (width, height) = self.bitmap.size();
+        Size::new(width, height)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl ImageDrawable for PageImage<'_> {
+    type Color = BinaryColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        // the top left of `area` is drawn at the origin of the target
+        let clipped = area.intersection(&self.bounding_box());
+        let bitmap = self.bitmap;
+        let colors = clipped
+            .rows()
+            .flat_map(|y| clipped.columns().map(move |x| bitmap.pixel(x, y).into()));
+
+        let destination = Rectangle::new(clipped.top_left - area.top_left, clipped.size);
+        target.fill_contiguous(&destination, colors)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl GetPixel for PageImage<'_> {
+    type Color = BinaryColor;
+
+    fn pixel(&self, p: Point) -> Option<BinaryColor> {
+        self.bounding_box()
+            .contains(p)
+            .then(|| self.bitmap.pixel(p.x, p.y).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{page_major, Bitmap, BitmapLayout};
+
+    /// 10x10 bitmap with a diagonal and a lit top row, in both layouts
+    const ROW_MAJOR: [u8; 20] = [
+        0xFF, 0xC0, 0x40, 0x00, 0x20, 0x00, 0x10, 0x00, 0x08, 0x00, //
+        0x04, 0x00, 0x02, 0x00, 0x01, 0x00, 0x00, 0x80, 0x00, 0x40,
+    ];
+    const PAGE_MAJOR: [u8; 20] = [
+        0x01, 0x03, 0x05, 0x09, 0x11, 0x21, 0x41, 0x81, 0x01, 0x01, //
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02,
+    ];
+
+    #[test]
+    fn layouts_agree() {
+        let rows = Bitmap::new(&ROW_MAJOR, 10, 10, BitmapLayout::RowMajor);
+        let pages = Bitmap::new(&PAGE_MAJOR, 10, 10, BitmapLayout::PageMajor);
+
+        for y in -9..12 {
+            for x in -9..12 {
+                assert_eq!(rows.pixel(x, y), pages.pixel(x, y));
+                assert_eq!(rows.column_bits(x, y), pages.column_bits(x, y));
+                assert_eq!(rows.row_bits(x, y), pages.row_bits(x, y));
+            }
+        }
+        assert_eq!(pages.column_bits(1, -1), 0x06);
+        assert_eq!(rows.row_bits(-3, 0), 0xF8);
+    }
+
+    #[test]
+    fn page_major_conversion() {
+        const PAGES: [u8; 20] = page_major(&ROW_MAJOR, 10, 10);
+
+        assert_eq!(PAGES, PAGE_MAJOR);
+    }
+}
@@ -0,0 +1,125 @@
+//! Perceptual brightness levels
+//!
+//! The light output of an OLED is roughly proportional to the contrast register, but the eye
+//! sees brightness on a much more compressed scale: going from contrast 8 to 16 is a big step,
+//! from 200 to 208 can hardly be seen. Brightness levels map to the contrast with the CIE 1931
+//! lightness curve, so that equal steps in level look like equal steps in brightness.
+//!
+//! [`GraphicsMode::set_brightness`](crate::mode::GraphicsMode::set_brightness) and
+//! [`GraphicsMode::fade_to`](crate::mode::GraphicsMode::fade_to) take brightness levels. Note
+//! that a contrast of `0` is dim, but doesn't turn the pixels off.
+
+/// Get the contrast that shows brightness `level`, from `0` for the lowest to `255` for the
+/// highest contrast
+pub const fn to_contrast(level: u8) -> u8 {
+    // lightness L* from 0 to 100, scaled by 255
+    let lightness = level as u64 * 100;
+
+    let contrast = if lightness <= 8 * 255 {
+        // linear near black, Y = L* / 903.3
+        lightness * 10 / 9033
+    } else {
+        // Y = ((L* + 16) / 116)^3, rounded
+        let x = lightness + 16 * 255;
+        let d = 116 * 255;
+        (255 * x * x * x + d * d * d / 2) / (d * d * d)
+    };
+
+    contrast as u8
+}
+
+/// Get the lowest brightness level shown at `contrast` or higher, the inverse of
+/// [`to_contrast`]
+pub fn from_contrast(contrast: u8) -> u8 {
+    let (mut low, mut high) = (0u16, 255u16);
+    while low < high {
+        let mid = (low + high) / 2;
+        if to_contrast(mid as u8) < contrast {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_contrast, to_contrast};
+
+    #[test]
+    fn levels_follow_lightness_curve() {
+        assert_eq!(to_contrast(0), 0);
+        assert_eq!(to_contrast(255), 255);
+        // half the lightness is less than a fifth of the light
+        assert_eq!(to_contrast(128), 47);
+
+        for level in 0..255 {
+            assert!(to_contrast(level) <= to_contrast(level + 1));
+        }
+        for contrast in 0..=255 {
+            let level = from_contrast(contrast);
+            assert!(to_contrast(level) >= contrast);
+            assert!(level == 0 || to_contrast(level - 1) < contrast);
+        }
+        assert_eq!(from_contrast(to_contrast(200)), 200);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fade_steps_contrast_over_time() {
+        use core::{cell::RefCell, convert::identity};
+        use std::vec::Vec;
+
+        #[cfg(feature = "blocking")]
+        use hal::delay::DelayNs;
+        #[cfg(not(feature = "blocking"))]
+        use embedded_hal_async::delay::DelayNs;
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64,
+            prelude::*,
+            sim::{block_on, Emulator},
+            test_helpers::setup,
+        };
+
+        /// Delay logging the time waited and the contrast shown while waiting
+        struct Log<'a> {
+            emulator: &'a RefCell<Emulator>,
+            waited: u64,
+            contrasts: Vec<u8>,
+        }
+
+        #[maybe_async_cfg::maybe(
+            sync(feature = "blocking", keep_self),
+            async(not(feature = "blocking"), keep_self)
+        )]
+        impl DelayNs for Log<'_> {
+            async fn delay_ns(&mut self, ns: u32) {
+                self.waited += ns as u64;
+                self.contrasts.push(self.emulator.borrow().contrast());
+            }
+        }
+
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let mut delay = Log {
+            emulator: &emulator,
+            waited: 0,
+            contrasts: Vec::new(),
+        };
+
+        block_on(display.set_brightness(0)).unwrap();
+        block_on(display.fade_to(200, 300, &mut delay)).unwrap();
+
+        assert_eq!(delay.waited, 300_000_000);
+        assert!(delay.contrasts.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(delay.contrasts.len() >= 30);
+        assert_eq!(emulator.borrow().contrast(), to_contrast(200));
+        assert_eq!(display.get_brightness(), 200);
+
+        // a fade without a duration sets the level right away
+        block_on(display.fade_to(10, 0, &mut delay)).unwrap();
+        assert_eq!(emulator.borrow().contrast(), to_contrast(10));
+    }
+}
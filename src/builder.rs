@@ -1,10 +1,10 @@
 //! Interface factory
 //!
 //! This is the easiest way to create a driver instance. You can set various parameters of the
-//! driver and give it an interface to use. The builder will return a
-//! [`mode::RawMode`](../mode/raw/struct.RawMode.html) object which you should coerce to a richer
-//! display mode, like [mode::Graphics](../mode/graphics/struct.GraphicsMode.html) for drawing
-//! primitives and text.
+//! driver and give it an interface to use. The builder will return an [`UninitDisplay`], which
+//! has to be reset and initialised before it can be used. Initialising it returns a display
+//! mode, like [`GraphicsMode`](crate::mode::GraphicsMode) for drawing primitives and text, or
+//! [`RawMode`](crate::mode::RawMode).
 //!
 //! # Examples
 //!
@@ -15,7 +15,7 @@
 //! let spi = /* Create an SPI 'device' that implements embedded_hal::SpiDevice  using a HAL of your choice */
 //! let di = /*  Use spi to create an interface that implements display_interface::AsyncWriteOnlyDataCommand using a bus that matches your hardware such as display_interface_spi::SPIInterface */
 //!
-//! let mut raw_display = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
+//! let uninit = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
 //!         .with_rotation(crate::DisplayRotation::Rotate180)
 //!         .connect(di);
 //! ```
@@ -24,28 +24,37 @@
 //! and SSH13xx families. It is intended to be easy to add additional specific
 //! display variants. This can be done by adding to the provided modules in
 //! src/displays (please submit a PR) or creating a new display variant out of
-//! tree in user crate.
+//! tree in user crate, most easily with the [`define_variant!`](crate::define_variant) macro.
 //!
-//! The above example will produce a [RawMode](../mode/raw/struct.RawMode.html) instance
-//! by default. You need to coerce them into a mode by specifying a type on assignment. For
-//! example, to use [`GraphicsMode` mode](../mode/graphics/struct.GraphicsMode.html):
+//! The mode is chosen by specifying a type on assignment. For example, to reset the display and
+//! use [`GraphicsMode`](crate::mode::GraphicsMode):
 //!
 //! ```rust,no_run
 //! use oled_async::{mode::GraphicsMode, Builder};
-//! let mut display: GraphicsMode<_, _> = raw_display.into();
+//! let mut display: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
 //! ```
+//!
+//! Displays without a reset pin, or that are already powered up, can skip the reset with
+//! [`UninitDisplay::init_without_reset`].
 
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
 
+use display_interface::DisplayError;
 use hal::digital::OutputPin;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
 
 use crate::{
-    displayrotation::DisplayRotation,
-    mode::{displaymode::DisplayMode, raw::RawMode},
+    display::InitConfig,
+    displayrotation::{DisplayRotation, Mirror},
+    mode::displaymode::DisplayModeTrait,
     properties::DisplayProperties,
+    Error,
 };
 
 /// Builder struct. Driver options and interface are set using its methods.
@@ -63,6 +72,8 @@ use crate::{
 pub struct Builder<DV> {
     variant: DV,
     rotation: DisplayRotation,
+    mirror: Mirror,
+    init_config: Option<InitConfig>,
 }
 
 impl<DV> Builder<DV> {
@@ -71,6 +82,8 @@ impl<DV> Builder<DV> {
         Builder::<DV> {
             variant,
             rotation: DisplayRotation::Rotate0,
+            mirror: Mirror::None,
+            init_config: None,
         }
     }
 }
@@ -89,14 +102,105 @@ impl<DV> Builder<DV> {
         Self { rotation, ..self }
     }
 
-    /// Finish the builder and use the given interface to communicate with the display.
-    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DV, DI>>
+    /// Mirror the display horizontally, vertically or both, along the axes of the rotated image.
+    /// Defaults to no mirroring.
+    pub fn with_mirror(self, mirror: Mirror) -> Self {
+        Self { mirror, ..self }
+    }
+
+    /// Set the parameters sent to the display during initialisation. Defaults to the values of
+    /// the display variant.
+    pub fn with_init_config(self, init_config: InitConfig) -> Self {
+        Self {
+            init_config: Some(init_config),
+            ..self
+        }
+    }
+
+    /// Finish the builder and use the given interface to communicate with the display. The
+    /// display has to be initialised before it can be used.
+    pub fn connect<DI>(self, interface: DI) -> UninitDisplay<DV, DI>
     where
         DI: AsyncWriteOnlyDataCommand,
-        DV: crate::display::DisplayVariant,
     {
-        let properties = DisplayProperties::new(self.variant, interface, self.rotation);
-        DisplayMode::<RawMode<DV, DI>>::new(properties)
+        let mut properties = DisplayProperties::new(self.variant, interface, self.rotation);
+        properties.set_mirror(self.mirror);
+        if let Some(init_config) = self.init_config {
+            properties.set_init_config(init_config);
+        }
+        UninitDisplay { properties }
+    }
+}
+
+/// A display that is connected but not initialised yet, returned by [`Builder::connect`].
+///
+/// Initialising it consumes it and returns the display in the requested mode, so a display mode
+/// can't be used before the display is set up.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub struct UninitDisplay<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    properties: DisplayProperties<DV, DI>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> UninitDisplay<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    /// Reset the display with its reset pin, then initialise it in `MODE`:
+    ///
+    /// ```rust,no_run
+    /// let mut display: GraphicsMode<_, _> = Builder::new(Sh1107_128_128 {})
+    ///     .connect(di)
+    ///     .init(&mut reset, &mut delay)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn init<MODE, RST, DELAY, PinE>(
+        self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<MODE, Error<DisplayError, PinE>>
+    where
+        MODE: DisplayModeTrait<DV, DI>,
+        RST: OutputPin<Error = PinE>,
+        DELAY: DelayNs,
+    {
+        rst.set_high().map_err(Error::Pin)?;
+        delay.delay_ms(1).await;
+        rst.set_low().map_err(Error::Pin)?;
+        delay.delay_ms(10).await;
+        rst.set_high().map_err(Error::Pin)?;
+
+        self.init_without_reset().await.map_err(Error::Comm)
+    }
+
+    /// Initialise the display in `MODE` without resetting it first, for displays without a reset
+    /// pin or that are reset some other way
+    pub async fn init_without_reset<MODE>(self) -> Result<MODE, DisplayError>
+    where
+        MODE: DisplayModeTrait<DV, DI>,
+    {
+        let mut mode = MODE::new(self.properties);
+        mode.init().await?;
+
+        Ok(mode)
     }
 }
 
@@ -121,20 +225,27 @@ impl hal::digital::ErrorType for NoOutputPin {
 #[cfg(test)]
 mod tests {
     use super::NoOutputPin;
-    use embedded_hal::digital::v2::OutputPin;
+    use core::convert::Infallible;
+    use embedded_hal::digital::OutputPin;
 
-    enum SomeError {}
+    struct SomeDriver<P: OutputPin<Error = Infallible>> {
+        p: Option<P>,
+    }
 
-    struct SomeDriver<P: OutputPin<Error = SomeError>> {
-        #[allow(dead_code)]
-        p: P,
+    impl<P: OutputPin<Error = Infallible>> SomeDriver<P> {
+        fn reset(&mut self) -> Result<(), Infallible> {
+            if let Some(p) = &mut self.p {
+                p.set_low()?;
+                p.set_high()?;
+            }
+            Ok(())
+        }
     }
 
     #[test]
     fn test_output_pin() {
-        let p = NoOutputPin::new();
-        let _d = SomeDriver { p };
+        let mut d = SomeDriver::<NoOutputPin> { p: None };
 
-        assert!(true);
+        assert!(d.reset().is_ok());
     }
 }
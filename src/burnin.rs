@@ -0,0 +1,228 @@
+//! Burn-in protection for displays showing the same image for a long time
+//!
+//! OLED pixels age with the time they are lit, so a static screen slowly burns into the panel.
+//! [`GraphicsMode::set_burn_in`](crate::mode::GraphicsMode::set_burn_in) enables protection
+//! that is driven by calling [`GraphicsMode::tick`](crate::mode::GraphicsMode::tick) with the
+//! current time from any clock, in milliseconds:
+//!
+//! - The image is moved around by up to `max_shift` pixels in each direction, a pixel at a time.
+//!   Vertical shifts only change the display start line, horizontal ones send the columns of
+//!   the image that change.
+//! - The contrast is lowered once there has been no activity for a while.
+//! - A screensaver starts after a longer idle time.
+//!
+//! ```rust,no_run
+//! use oled_async::burnin::{BurnInConfig, Screensaver};
+//!
+//! let config = BurnInConfig::new()
+//!     .with_pixel_shift(60_000, 2)
+//!     .with_dimming(5 * 60_000, 0x10)
+//!     .with_screensaver(30 * 60_000, Screensaver::Sleep);
+//! display.set_burn_in(Some(config), now(), &mut delay).await?;
+//!
+//! loop {
+//!     if button_pressed() {
+//!         display.activity(now(), &mut delay).await?;
+//!     }
+//!     display.tick(now(), &mut delay).await?;
+//! }
+//! ```
+//!
+//! Shifting wraps the image around the bottom edge of the display, and pushes columns past the
+//! right edge out of view, so it works best with a blank border of `max_shift` pixels.
+
+/// What happens when the display has been idle for the screensaver timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screensaver {
+    /// Put the display to sleep until the next activity
+    Sleep,
+    /// Keep the display on and report [`IdleState::Screensaver`] from `tick()`, so that the
+    /// application can draw its own screensaver
+    Custom,
+}
+
+/// How long the display has been idle, as returned by `tick()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleState {
+    /// The display is shown normally
+    Active,
+    /// The display is dimmed
+    Dimmed,
+    /// The screensaver is running
+    Screensaver,
+}
+
+/// Settings of the burn-in protection
+#[derive(Debug, Clone, Copy)]
+pub struct BurnInConfig {
+    shift_interval: u64,
+    max_shift: u8,
+    dimming: Option<(u64, u8)>,
+    screensaver: Option<(u64, Screensaver)>,
+}
+
+impl Default for BurnInConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BurnInConfig {
+    /// Shift the image by up to 2 pixels every minute, without dimming or screensaver
+    pub const fn new() -> Self {
+        BurnInConfig {
+            shift_interval: 60_000,
+            max_shift: 2,
+            dimming: None,
+            screensaver: None,
+        }
+    }
+
+    /// Move the image by a pixel every `interval` milliseconds, up to `max_shift` pixels in
+    /// each direction. A `max_shift` of `0` disables shifting.
+    pub const fn with_pixel_shift(self, interval: u64, max_shift: u8) -> Self {
+        BurnInConfig {
+            shift_interval: interval,
+            max_shift,
+            ..self
+        }
+    }
+
+    /// Lower the contrast to `contrast` after `idle` milliseconds without activity
+    pub const fn with_dimming(self, idle: u64, contrast: u8) -> Self {
+        BurnInConfig {
+            dimming: Some((idle, contrast)),
+            ..self
+        }
+    }
+
+    /// Start the `screensaver` after `idle` milliseconds without activity
+    pub const fn with_screensaver(self, idle: u64, screensaver: Screensaver) -> Self {
+        BurnInConfig {
+            screensaver: Some((idle, screensaver)),
+            ..self
+        }
+    }
+
+    /// Get the contrast of the dimmed display, if dimming is enabled
+    pub(crate) fn dim_contrast(&self) -> Option<u8> {
+        self.dimming.map(|(_, contrast)| contrast)
+    }
+
+    /// Get whether the display sleeps in `state`
+    pub(crate) fn sleeps(&self, state: IdleState) -> bool {
+        state == IdleState::Screensaver
+            && matches!(self.screensaver, Some((_, Screensaver::Sleep)))
+    }
+
+    /// Get the state of a display that has been idle for `idle` milliseconds
+    fn idle_state(&self, idle: u64) -> IdleState {
+        match (self.screensaver, self.dimming) {
+            (Some((after, _)), _) if idle >= after => IdleState::Screensaver,
+            (_, Some((after, _))) if idle >= after => IdleState::Dimmed,
+            _ => IdleState::Active,
+        }
+    }
+
+    /// Get the shift of the image at `step`, walking back and forth over all shifts a pixel at
+    /// a time
+    fn shift_at(&self, step: u32) -> (u8, u8) {
+        let side = self.max_shift as u32 + 1;
+        let positions = side * side;
+        if positions == 1 {
+            return (0, 0);
+        }
+
+        let period = 2 * positions - 2;
+        let mut i = step % period;
+        if i >= positions {
+            i = period - i;
+        }
+        let (row, column) = (i / side, i % side);
+        // every other row is walked backwards, so that each step moves by one pixel
+        let column = if row % 2 == 1 { side - 1 - column } else { column };
+
+        (column as u8, row as u8)
+    }
+}
+
+/// Burn-in protection state of a display
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BurnIn {
+    pub(crate) config: BurnInConfig,
+    pub(crate) state: IdleState,
+    last_activity: u64,
+    last_shift: u64,
+    step: u32,
+}
+
+impl BurnIn {
+    /// Start protecting a display that was last used at `now`
+    pub(crate) fn new(config: BurnInConfig, now: u64) -> Self {
+        BurnIn {
+            config,
+            state: IdleState::Active,
+            last_activity: now,
+            last_shift: now,
+            step: 0,
+        }
+    }
+
+    /// Record activity at `now`
+    pub(crate) fn activity(&mut self, now: u64) {
+        self.last_activity = now;
+    }
+
+    /// Get the idle state at `now`
+    pub(crate) fn idle_state(&self, now: u64) -> IdleState {
+        self.config
+            .idle_state(now.saturating_sub(self.last_activity))
+    }
+
+    /// Advance the image shift if it's due at `now`, and return the new shift
+    pub(crate) fn shift(&mut self, now: u64) -> Option<(u8, u8)> {
+        let config = &self.config;
+        if config.max_shift == 0 || now.saturating_sub(self.last_shift) < config.shift_interval {
+            return None;
+        }
+
+        self.last_shift = now;
+        self.step = self.step.wrapping_add(1);
+        Some(config.shift_at(self.step))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BurnInConfig, IdleState, Screensaver};
+
+    #[test]
+    fn shift_walks_a_pixel_at_a_time() {
+        let config = BurnInConfig::new().with_pixel_shift(1, 2);
+
+        let shifts: [(u8, u8); 10] = core::array::from_fn(|step| config.shift_at(step as u32));
+        assert_eq!(
+            shifts,
+            [(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1), (0, 2), (1, 2), (2, 2), (1, 2)]
+        );
+        for step in 0..40 {
+            let (x0, y0) = config.shift_at(step);
+            let (x1, y1) = config.shift_at(step + 1);
+            assert_eq!(x0.abs_diff(x1) + y0.abs_diff(y1), 1);
+            assert!(x0 <= 2 && y0 <= 2);
+        }
+        assert_eq!(BurnInConfig::new().with_pixel_shift(1, 0).shift_at(3), (0, 0));
+    }
+
+    #[test]
+    fn idle_state_follows_timeouts() {
+        let config = BurnInConfig::new()
+            .with_dimming(100, 0x10)
+            .with_screensaver(300, Screensaver::Custom);
+
+        assert_eq!(config.idle_state(99), IdleState::Active);
+        assert_eq!(config.idle_state(100), IdleState::Dimmed);
+        assert_eq!(config.idle_state(300), IdleState::Screensaver);
+        assert_eq!(BurnInConfig::new().idle_state(u64::MAX), IdleState::Active);
+    }
+}
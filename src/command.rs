@@ -4,10 +4,13 @@ use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::WriteOnlyDataCommand;
 use display_interface::{DataFormat, DisplayError};
 
+/// Most bytes sent in one transaction by [`Command::send_batch`], enough for a whole init sequence
+const BATCH_SIZE: usize = 32;
+
 /// oled_async Commands
 
 /// Commands
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 pub enum Command {
     /// Set the addressing mode.
@@ -35,6 +38,8 @@ pub enum Command {
     LargePageAddress(u8),
     /// Set display start line from 0-63
     StartLine(u8),
+    /// Set display start line (large variant for controllers with more than 64 rows)
+    LargeStartLine(u8),
     /// Reverse columns from 127-0
     SegmentRemap(bool),
     /// Set multipex ratio from 15-63 (MUX-1)
@@ -62,6 +67,48 @@ pub enum Command {
     Noop,
     /// Enable charge pump
     ChargePump(bool),
+    /// Enable charge pump (SSD1306 specific command)
+    Ssd1306ChargePump(bool),
+    /// Set row address (SH1122 specific command)
+    RowAddress(u8),
+    /// Set column start and end address of the drawing window (SSD1327 specific command)
+    ColumnAddressRange(u8, u8),
+    /// Set row start and end address of the drawing window (SSD1327 specific command)
+    RowAddressRange(u8, u8),
+    /// Set re-map and dual COM line mode (SSD1327 specific command)
+    Ssd1327Remap(u8),
+    /// Set display start line (SSD1327 specific command)
+    Ssd1327StartLine(u8),
+    /// Set vertical shift (SSD1327 specific command)
+    Ssd1327DisplayOffset(u8),
+    /// Set phase 1 and 2 period length (SSD1327 specific command)
+    Ssd1327PhaseLength(u8, u8),
+    /// Set up display clock (SSD1327 specific command)
+    /// First value is oscillator frequency, second value is divide ratio - 1
+    Ssd1327ClockDiv(u8, u8),
+    /// Set pre-charge voltage (SSD1327 specific command)
+    Ssd1327PreChargeVoltage(u8),
+    /// Set VCOMH voltage (SSD1327 specific command)
+    Ssd1327Vcomh(u8),
+    /// Set second pre-charge period (SSD1327 specific command)
+    Ssd1327SecondPreCharge(u8),
+    /// Setup continuous horizontal scroll of pages start..=end (SSD1306 specific command)
+    HScrollSetup(HScrollDir, u8, u8, NFrames),
+    /// Setup continuous vertical and horizontal scroll of pages start..=end with a vertical
+    /// offset in rows per step (SSD1306 specific command)
+    VHScrollSetup(VHScrollDir, u8, u8, NFrames, u8),
+    /// Setup continuous horizontal scroll of pages start..=end, limited to the columns given by
+    /// the last two values (SSD1309 specific command)
+    Ssd1309HScrollSetup(HScrollDir, u8, u8, NFrames, u8, u8),
+    /// Setup continuous vertical and horizontal scroll of pages start..=end with a vertical
+    /// offset in rows per step, limited to the columns given by the last two values (SSD1309
+    /// specific command)
+    Ssd1309VHScrollSetup(VHScrollDir, u8, u8, NFrames, u8, u8, u8),
+    /// Activate or deactivate scrolling (SSD130x specific command)
+    EnableScroll(bool),
+    /// Set vertical scroll area. First value is the number of fixed rows at the top, second value
+    /// is the number of rows in the scroll area (SSD130x specific command)
+    VScrollArea(u8, u8),
 }
 
 #[maybe_async_cfg::maybe(
@@ -78,38 +125,117 @@ impl Command {
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        // Transform command into a fixed size array of 2 u8 (seems to be the maximum) and the real length for sending
-        let (data, len) = match self {
-            Command::AddressMode(mode) => ([0x20 | (mode as u8), 0], 1),
-            Command::Contrast(val) => ([0x81, val], 2),
-            Command::AllOn(on) => ([0xA4 | (on as u8), 0], 1),
-            Command::Invert(inv) => ([0xA6 | (inv as u8), 0], 1),
-            Command::DisplayResolution(resolution) => ([0xA9, resolution], 2),
-            Command::DisplayOn(on) => ([0xAE | (on as u8), 0], 1),
-            Command::ColumnAddressLow(addr) => ([0xF & addr, 0], 1),
-            Command::ColumnAddressHigh(addr) => ([0x10 | (0xF & addr), 0], 1),
-            Command::PageAddress(page) => ([0xB0 | (page & 0xF), 0], 1),
-            Command::LargePageAddress(page) => ([0xB0, page], 2),
-            Command::StartLine(line) => ([0x40 | (0x3F & line), 0], 1),
-            Command::SegmentRemap(remap) => ([0xA0 | (remap as u8), 0], 1),
-            Command::Multiplex(ratio) => ([0xA8, ratio], 2),
-            Command::ReverseComDir(rev) => ([0xC0 | ((rev as u8) << 3), 0], 1),
-            Command::DisplayOffset(offset) => ([0xD3, offset], 2),
-            Command::ComPinConfig(alt) => ([0xDA, 0x02 | ((alt as u8) << 4)], 2),
-            Command::DisplayClockDiv(fosc, div) => ([0xD5, ((0xF & fosc) << 4) | (0xF & div)], 2),
-            Command::PreChargePeriod(phase1, phase2) => ([0xD9, ((0xF & phase2) << 4) | (0xF & phase1)], 2),
-            Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 4], 2),
-            Command::Noop => ([0xE3, 0], 1),
-            Command::ChargePump(en) => ([0xAD, 0x8A | (en as u8)], 2),
-        };
+        let (data, len) = self.encode();
         // Send command over the interface
         iface.send_commands(DataFormat::U8(&data[..len])).await
     }
+
+    /// Send several commands to oled_async in as few transactions as possible, instead of one
+    /// transaction per command. Saves a start condition and address byte per command over I2C.
+    pub async fn send_batch<DI, I>(commands: I, iface: &mut DI) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        I: IntoIterator<Item = Command>,
+    {
+        let mut data = [0; BATCH_SIZE];
+        let mut len = 0;
+        for command in commands {
+            let (bytes, n) = command.encode();
+            if len + n > data.len() {
+                iface.send_commands(DataFormat::U8(&data[..len])).await?;
+                len = 0;
+            }
+            data[len..len + n].copy_from_slice(&bytes[..n]);
+            len += n;
+        }
+
+        if len > 0 {
+            iface.send_commands(DataFormat::U8(&data[..len])).await?;
+        }
+        Ok(())
+    }
+
+    fn encode(self) -> ([u8; 8], usize) {
+        // Transform command into a fixed size array of 3 u8 (enough for all but scroll setup) and the real length for sending
+        let (data, len) = match self {
+            Command::AddressMode(mode) => ([0x20 | (mode as u8), 0, 0], 1),
+            Command::Contrast(val) => ([0x81, val, 0], 2),
+            Command::AllOn(on) => ([0xA4 | (on as u8), 0, 0], 1),
+            Command::Invert(inv) => ([0xA6 | (inv as u8), 0, 0], 1),
+            Command::DisplayResolution(resolution) => ([0xA9, resolution, 0], 2),
+            Command::DisplayOn(on) => ([0xAE | (on as u8), 0, 0], 1),
+            Command::ColumnAddressLow(addr) => ([0xF & addr, 0, 0], 1),
+            Command::ColumnAddressHigh(addr) => ([0x10 | (0xF & addr), 0, 0], 1),
+            Command::PageAddress(page) => ([0xB0 | (page & 0xF), 0, 0], 1),
+            Command::LargePageAddress(page) => ([0xB0, page, 0], 2),
+            Command::StartLine(line) => ([0x40 | (0x3F & line), 0, 0], 1),
+            Command::LargeStartLine(line) => ([0xDC, line, 0], 2),
+            Command::SegmentRemap(remap) => ([0xA0 | (remap as u8), 0, 0], 1),
+            Command::Multiplex(ratio) => ([0xA8, ratio, 0], 2),
+            Command::ReverseComDir(rev) => ([0xC0 | ((rev as u8) << 3), 0, 0], 1),
+            Command::DisplayOffset(offset) => ([0xD3, offset, 0], 2),
+            Command::ComPinConfig(alt) => ([0xDA, 0x02 | ((alt as u8) << 4), 0], 2),
+            Command::DisplayClockDiv(fosc, div) => ([0xD5, ((0xF & fosc) << 4) | (0xF & div), 0], 2),
+            Command::PreChargePeriod(phase1, phase2) => ([0xD9, ((0xF & phase2) << 4) | (0xF & phase1), 0], 2),
+            Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 4, 0], 2),
+            Command::Noop => ([0xE3, 0, 0], 1),
+            Command::ChargePump(en) => ([0xAD, 0x8A | (en as u8), 0], 2),
+            Command::Ssd1306ChargePump(en) => ([0x8D, 0x10 | ((en as u8) << 2), 0], 2),
+            Command::RowAddress(row) => ([0xB0, row, 0], 2),
+            Command::ColumnAddressRange(start, end) => ([0x15, start, end], 3),
+            Command::RowAddressRange(start, end) => ([0x75, start, end], 3),
+            Command::Ssd1327Remap(remap) => ([0xA0, remap, 0], 2),
+            Command::Ssd1327StartLine(line) => ([0xA1, 0x7F & line, 0], 2),
+            Command::Ssd1327DisplayOffset(offset) => ([0xA2, 0x7F & offset, 0], 2),
+            Command::Ssd1327PhaseLength(phase1, phase2) => ([0xB1, ((0xF & phase2) << 4) | (0xF & phase1), 0], 2),
+            Command::Ssd1327ClockDiv(fosc, div) => ([0xB3, ((0xF & fosc) << 4) | (0xF & div), 0], 2),
+            Command::Ssd1327PreChargeVoltage(level) => ([0xBC, 0xF & level, 0], 2),
+            Command::Ssd1327Vcomh(level) => ([0xBE, 0xF & level, 0], 2),
+            Command::Ssd1327SecondPreCharge(period) => ([0xB6, 0xF & period, 0], 2),
+            Command::EnableScroll(en) => ([0x2E | (en as u8), 0, 0], 1),
+            Command::VScrollArea(above, lines) => ([0xA3, above, lines], 3),
+            // scroll setup doesn't fit in 3 bytes
+            Command::HScrollSetup(dir, start, end, rate) => {
+                return ([0x26 | (dir as u8), 0, start, rate as u8, end, 0, 0xFF, 0], 7)
+            }
+            Command::VHScrollSetup(dir, start, end, rate, offset) => {
+                return ([0x28 | (dir as u8), 0, start, rate as u8, end, offset, 0, 0], 6)
+            }
+            Command::Ssd1309HScrollSetup(dir, start, end, rate, first, last) => {
+                return ([0x26 | (dir as u8), 0, start, rate as u8, end, 0, first, last], 8)
+            }
+            // horizontal scrolling is optional here, always enabled to match the SSD1306
+            Command::Ssd1309VHScrollSetup(dir, start, end, rate, offset, first, last) => {
+                return ([0x28 | (dir as u8), 1, start, rate as u8, end, offset, first, last], 8)
+            }
+        };
+
+        let mut bytes = [0; 8];
+        bytes[..3].copy_from_slice(&data);
+        (bytes, len)
+    }
+}
+
+/// Horizontal scroll direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HScrollDir {
+    /// Left to right
+    LeftToRight = 0,
+    /// Right to left
+    RightToLeft = 1,
+}
+
+/// Vertical and horizontal scroll direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VHScrollDir {
+    /// Vertical and right horizontal
+    VerticalRight = 0b01,
+    /// Vertical and left horizontal
+    VerticalLeft = 0b10,
 }
 
 /// Frame interval
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum NFrames {
     /// 2 Frames
     F2 = 0b111,
@@ -142,3 +268,51 @@ pub enum VcomhLevel {
     /// Auto
     Auto = 0b100,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, HScrollDir, NFrames, VHScrollDir};
+
+    fn assert_encodes(command: Command, expected: &[u8]) {
+        let (data, len) = command.encode();
+        assert_eq!(&data[..len], expected);
+    }
+
+    #[test]
+    fn scroll_commands_encode_per_controller() {
+        assert_encodes(
+            Command::HScrollSetup(HScrollDir::RightToLeft, 2, 5, NFrames::F64),
+            &[0x27, 0x00, 2, 0b001, 5, 0x00, 0xFF],
+        );
+        assert_encodes(
+            Command::VHScrollSetup(VHScrollDir::VerticalLeft, 0, 7, NFrames::F2, 3),
+            &[0x2A, 0x00, 0, 0b111, 7, 3],
+        );
+        assert_encodes(
+            Command::Ssd1309HScrollSetup(HScrollDir::LeftToRight, 1, 6, NFrames::F5, 0, 127),
+            &[0x26, 0x00, 1, 0b000, 6, 0x00, 0, 127],
+        );
+        assert_encodes(
+            Command::Ssd1309VHScrollSetup(VHScrollDir::VerticalRight, 0, 7, NFrames::F25, 1, 0, 99),
+            &[0x29, 0x01, 0, 0b110, 7, 1, 0, 99],
+        );
+        assert_encodes(Command::VScrollArea(8, 56), &[0xA3, 8, 56]);
+        assert_encodes(Command::EnableScroll(true), &[0x2F]);
+        assert_encodes(Command::EnableScroll(false), &[0x2E]);
+    }
+
+    #[test]
+    fn grayscale_commands_encode() {
+        assert_encodes(Command::ColumnAddressRange(5, 63), &[0x15, 5, 63]);
+        assert_encodes(Command::RowAddressRange(0, 127), &[0x75, 0, 127]);
+        assert_encodes(Command::RowAddress(42), &[0xB0, 42]);
+        assert_encodes(Command::Ssd1327Remap(0x51), &[0xA0, 0x51]);
+        assert_encodes(Command::Ssd1327StartLine(0xFF), &[0xA1, 0x7F]);
+        assert_encodes(Command::Ssd1327DisplayOffset(0x80), &[0xA2, 0x00]);
+        assert_encodes(Command::Ssd1327PhaseLength(0x1, 0x5), &[0xB1, 0x51]);
+        assert_encodes(Command::Ssd1327ClockDiv(0x0, 0x1), &[0xB3, 0x01]);
+        assert_encodes(Command::Ssd1327PreChargeVoltage(0x18), &[0xBC, 0x08]);
+        assert_encodes(Command::Ssd1327Vcomh(0x7), &[0xBE, 0x07]);
+        assert_encodes(Command::Ssd1327SecondPreCharge(0x1), &[0xB6, 0x01]);
+    }
+}
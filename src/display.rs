@@ -5,8 +5,57 @@ use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+pub use crate::command::VcomhLevel;
+use crate::{displays::generic::Controller, scroll::ScrollConfig};
+
+/// Time the charge pump needs to reach its output voltage after being switched on, in
+/// milliseconds
+pub(crate) const CHARGE_PUMP_SETTLE_MS: u32 = 100;
+
+/// Parameters sent to the display during initialisation
+///
+/// Each display variant supplies its own defaults through
+/// [`DisplayVariant::default_init_config`], these can be overridden with
+/// [`Builder::with_init_config`](crate::Builder::with_init_config).
+#[derive(Debug, Clone, Copy)]
+pub struct InitConfig {
+    /// Contrast, higher number is higher contrast
+    pub contrast: u8,
+    /// Phase 1 and 2 of the precharge period, each value is from 0-15. The SSD1327 takes them as
+    /// its phase lengths, its precharge voltage and second precharge period are fixed.
+    pub precharge: (u8, u8),
+    /// Vcomh deselect level. The SSD1327 only has levels of 0.72, 0.82 and 0.86 * Vcc, the
+    /// nearest one is used and `Auto` picks 0.86 * Vcc.
+    pub vcomh: VcomhLevel,
+    /// Oscillator frequency and divide ratio - 1 of the display clock, each value is from 0-15
+    pub clock_div: (u8, u8),
+    /// Use the internal charge pump. Turn off for panels running on an external VCC. Ignored by
+    /// the SSD1327, which has no charge pump.
+    pub charge_pump: bool,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        InitConfig {
+            contrast: 0x80,
+            precharge: (0x1, 0xF),
+            vcomh: VcomhLevel::Auto,
+            clock_div: (0x8, 0x0),
+            charge_pump: true,
+        }
+    }
+}
 
 /// Trait to represent a speciffic display
+///
+/// The associated consts describe the geometry of the display. Code driving the display reads it
+/// through the methods taking `&self`, so that a variant like
+/// [`RuntimeVariant`](crate::displays::generic::RuntimeVariant) can pick its geometry at runtime.
 #[maybe_async_cfg::maybe(
     sync(
         feature = "blocking",
@@ -24,15 +73,172 @@ pub trait DisplayVariant {
     const COLUMN_OFFSET: u8 = 0;
     /// Large Page Address
     const LARGE_PAGE_ADDRESS: bool = false;
+    /// Size in bytes of a framebuffer covering the whole display
+    const BUFFER_SIZE: usize = Self::WIDTH as usize * Self::HEIGHT.div_ceil(8) as usize;
+
+    /// Framebuffer used by [`GraphicsMode`](crate::mode::GraphicsMode) unless another one is
+    /// picked, normally `[u8; Self::BUFFER_SIZE]`
+    type Buffer: AsRef<[u8]> + AsMut<[u8]>;
+
+    /// Page buffer used by [`PagedMode`](crate::mode::PagedMode) unless another one is picked,
+    /// normally `[u8; Self::WIDTH as usize]`
+    type PageBuffer: AsRef<[u8]> + AsMut<[u8]>;
 
     /// Get integral dimensions from DisplaySize
     fn dimensions() -> (u8, u8) {
         (Self::WIDTH, Self::HEIGHT)
     }
 
+    /// Width of this display. Defaults to [`Self::WIDTH`], variants that only know their
+    /// geometry at runtime override this.
+    fn width(&self) -> u8 {
+        Self::WIDTH
+    }
+
+    /// Height of this display. Defaults to [`Self::HEIGHT`]
+    fn height(&self) -> u8 {
+        Self::HEIGHT
+    }
+
+    /// Column offset of this display. Defaults to [`Self::COLUMN_OFFSET`]
+    fn column_offset(&self) -> u8 {
+        Self::COLUMN_OFFSET
+    }
+
+    /// Large page address of this display. Defaults to [`Self::LARGE_PAGE_ADDRESS`]
+    fn large_page_address(&self) -> bool {
+        Self::LARGE_PAGE_ADDRESS
+    }
+
+    /// Controller family of this display, picks the sleep and scroll sequences
+    fn controller(&self) -> Controller;
+
+    /// Initialisation parameters used unless overridden with
+    /// [`Builder::with_init_config`](crate::Builder::with_init_config)
+    fn default_init_config(&self) -> InitConfig {
+        InitConfig::default()
+    }
+
     /// Initialise the display for column mode
     #[allow(async_fn_in_trait)]
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand;
+
+    /// Turn the panel off and put the controller to sleep, or wake it up again. Display RAM is
+    /// kept while asleep. Waking up waits on `delay` for the charge pump to settle before the
+    /// panel is turned on.
+    ///
+    /// Defaults to the sequence of the [`controller`](Self::controller) family, which switches
+    /// the charge pump off after the panel and back on before it.
+    #[allow(async_fn_in_trait)]
+    async fn set_sleep<DI, DELAY>(
+        &self,
+        iface: &mut DI,
+        sleep: bool,
+        config: &InitConfig,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        DELAY: DelayNs,
+    {
+        match self.controller() {
+            Controller::Ssd1306 => {
+                crate::displays::ssd1306::set_sleep_common(iface, sleep, config, delay).await
+            }
+            _ => crate::displays::sh1107::set_sleep_common(iface, sleep, config, delay).await,
+        }
+    }
+
+    /// Set up a continuous hardware scroll without starting it.
+    ///
+    /// Defaults to the commands of the [`controller`](Self::controller) family. The SSD1309
+    /// takes a column range as well, and the SH110x controllers can't scroll in hardware at all.
+    #[allow(async_fn_in_trait)]
+    async fn setup_scroll<DI>(
+        &self,
+        iface: &mut DI,
+        config: &ScrollConfig,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        match self.controller() {
+            Controller::Ssd1309 => {
+                crate::displays::ssd1309::setup_scroll_common(iface, config, self.width()).await
+            }
+            _ => crate::displays::ssd1306::setup_scroll_common(iface, config).await,
+        }
+    }
+}
+
+/// Trait to represent a speciffic display with a 4 bit per pixel grayscale controller
+///
+/// Unlike [`DisplayVariant`], the display memory of these controllers is addressed by row and
+/// column rather than by page. Two horizontally adjacent pixels are packed into each byte, the
+/// left pixel in the high nibble.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub trait GrayscaleDisplayVariant {
+    /// Width of display
+    const WIDTH: u16;
+    /// Height of display
+    const HEIGHT: u16;
+    /// Size in bytes of a framebuffer covering the whole display, two pixels per byte
+    const BUFFER_SIZE: usize = Self::WIDTH as usize * Self::HEIGHT as usize / 2;
+
+    /// Get integral dimensions
+    fn dimensions() -> (u16, u16) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Initialisation parameters used unless overridden with
+    /// [`Builder::with_init_config`](crate::Builder::with_init_config)
+    fn default_init_config() -> InitConfig;
+
+    /// Initialise the display. Fields of `config` the controller has no setting for are ignored.
+    #[allow(async_fn_in_trait)]
+    async fn init<DI>(iface: &mut DI, config: &InitConfig) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand;
+
+    /// Turn the panel off and put the controller to sleep, or wake it up again. Display RAM is
+    /// kept while asleep. Controllers with a charge pump switch it along with the panel and wait
+    /// on `delay` for it to settle when waking up.
+    #[allow(async_fn_in_trait)]
+    async fn set_sleep<DI, DELAY>(
+        iface: &mut DI,
+        sleep: bool,
+        config: &InitConfig,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        DELAY: DelayNs;
+
+    /// Send a window of nibble packed pixel data to the display.
+    ///
+    /// The window starts at column `x` and row `y` and is `width` pixels wide, both `x` and
+    /// `width` are even. `buf` holds whole rows of `width / 2` bytes each.
+    #[allow(async_fn_in_trait)]
+    async fn draw_window<DI>(
+        iface: &mut DI,
+        x: u16,
+        y: u16,
+        width: u16,
+        buf: &[u8],
+    ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand;
 }
@@ -1,4 +1,12 @@
-//! Display rotation
+//! Display rotation and mirroring
+//!
+//! Rotating by 180 degrees flips both axes in hardware. Rotating by 90 or 270 degrees swaps the
+//! x and y coordinates in software when drawing and flips one axis in hardware, which together
+//! turn the image a quarter turn rather than transposing it.
+//!
+//! A [`Mirror`] is applied on top of the rotation, along the axes of the rotated image. Every
+//! combination of the two only changes which axes are flipped in hardware, so mirroring costs
+//! nothing when drawing.
 
 /// Display rotation
 #[derive(Clone, Copy)]
@@ -12,3 +20,28 @@ pub enum DisplayRotation {
     /// Rotate 270 degress clockwise
     Rotate270,
 }
+
+/// Display mirroring, e.g. for displays seen through a beam splitter or reflected in a HUD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mirror {
+    /// No mirroring, normal display
+    None,
+    /// Mirror left to right
+    Horizontal,
+    /// Mirror top to bottom
+    Vertical,
+    /// Mirror both ways, the same as rotating by another 180 degrees
+    Both,
+}
+
+impl Mirror {
+    /// Get whether the x and y axes of the rotated image are flipped
+    pub(crate) fn flips(self) -> (bool, bool) {
+        match self {
+            Mirror::None => (false, false),
+            Mirror::Horizontal => (true, false),
+            Mirror::Vertical => (false, true),
+            Mirror::Both => (true, true),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Display variant with its geometry picked at runtime
+//!
+//! Firmware that detects the fitted panel at boot, e.g. from a strap pin or EEPROM, can describe
+//! it with a [`RuntimeVariant`] and drive any of the supported panels from a single binary:
+//!
+//! ```rust,no_run
+//! use oled_async::{displays::generic::{Controller, RuntimeVariant}, Builder};
+//!
+//! let variant = if strap_pin_high {
+//!     RuntimeVariant::new(Controller::Ssd1306, 128, 32).with_alternative_com_pins(false)
+//! } else {
+//!     RuntimeVariant::new(Controller::Sh1106, 128, 64).with_column_offset(2)
+//! };
+//! let uninit = Builder::new(variant).connect(di);
+//! let mut display: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
+//! ```
+
+use crate::display::{DisplayVariant, InitConfig};
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+
+use crate::command::Command;
+
+/// Controller family of a [`RuntimeVariant`], selects the initialisation sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// SH1106 controller
+    Sh1106,
+    /// SH1107 controller
+    Sh1107,
+    /// SH1108 controller with the given display resolution setting
+    Sh1108(u8),
+    /// SSD1306 controller
+    Ssd1306,
+    /// SSD1309 controller
+    Ssd1309,
+}
+
+/// Display variant carrying its geometry and init parameters as values
+///
+/// The associated `WIDTH` and `HEIGHT` consts hold the largest geometry a `RuntimeVariant` can
+/// describe, the actual geometry is returned by [`DisplayVariant::width`] and
+/// [`DisplayVariant::height`].
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeVariant {
+    controller: Controller,
+    width: u8,
+    height: u8,
+    column_offset: u8,
+    large_page_address: bool,
+    display_offset: u8,
+    alternative_com_pins: bool,
+}
+
+impl RuntimeVariant {
+    /// Create a new variant for a `width` x `height` display driven by `controller`, with no
+    /// offsets and alternative COM pins. SH1108 controllers default to large page addresses.
+    ///
+    /// Panics if the display is larger than 160x160 pixels.
+    pub const fn new(controller: Controller, width: u8, height: u8) -> Self {
+        assert!(width <= 160 && height <= 160);
+
+        RuntimeVariant {
+            controller,
+            width,
+            height,
+            column_offset: 0,
+            large_page_address: matches!(controller, Controller::Sh1108(_)),
+            display_offset: 0,
+            alternative_com_pins: true,
+        }
+    }
+
+    /// Set the column offset of the visible area in display RAM
+    pub const fn with_column_offset(self, column_offset: u8) -> Self {
+        Self {
+            column_offset,
+            ..self
+        }
+    }
+
+    /// Use the two byte page address command
+    pub const fn with_large_page_address(self, large_page_address: bool) -> Self {
+        Self {
+            large_page_address,
+            ..self
+        }
+    }
+
+    /// Set the vertical shift sent with the `DisplayOffset` command
+    pub const fn with_display_offset(self, display_offset: u8) -> Self {
+        Self {
+            display_offset,
+            ..self
+        }
+    }
+
+    /// Select alternative (`true`) or sequential (`false`) COM pin configuration
+    pub const fn with_alternative_com_pins(self, alternative_com_pins: bool) -> Self {
+        Self {
+            alternative_com_pins,
+            ..self
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for RuntimeVariant {
+    const WIDTH: u8 = 160;
+    const HEIGHT: u8 = 160;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        self.controller
+    }
+
+    fn width(&self) -> u8 {
+        self.width
+    }
+
+    fn height(&self) -> u8 {
+        self.height
+    }
+
+    fn column_offset(&self) -> u8 {
+        self.column_offset
+    }
+
+    fn large_page_address(&self) -> bool {
+        self.large_page_address
+    }
+
+    fn default_init_config(&self) -> InitConfig {
+        match self.controller {
+            Controller::Sh1108(_) => super::sh1108::default_init_config(),
+            _ => InitConfig::default(),
+        }
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        let dimensions = (self.width, self.height);
+
+        match self.controller {
+            Controller::Sh1106 | Controller::Sh1107 => {
+                super::sh1107::init_column_mode_common(iface, dimensions, config).await?
+            }
+            Controller::Sh1108(resolution) => {
+                super::sh1108::init_column_mode_common(iface, dimensions, resolution, config)
+                    .await?
+            }
+            Controller::Ssd1306 => {
+                super::ssd1306::init_column_mode_common(
+                    iface,
+                    dimensions,
+                    self.alternative_com_pins,
+                    config,
+                )
+                .await?
+            }
+            Controller::Ssd1309 => {
+                super::ssd1309::init_column_mode_common(iface, dimensions, config).await?
+            }
+        }
+        Command::send_batch(
+            [
+                Command::DisplayOffset(self.display_offset),
+                Command::ComPinConfig(self.alternative_com_pins),
+            ],
+            iface,
+        )
+        .await
+    }
+}
@@ -3,7 +3,11 @@
 //! This driver can be used in different modes. A mode defines how the driver will behave, and what
 //! methods it exposes. Look at the modes below for more information on what they expose.
 
+pub mod generic;
 pub mod sh1106;
 pub mod sh1107;
 pub mod sh1108;
+pub mod sh1122;
+pub mod ssd1306;
 pub mod ssd1309;
+pub mod ssd1327;
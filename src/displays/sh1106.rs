@@ -1,6 +1,10 @@
 //! SH1106 display variant
 
-use crate::{display::DisplayVariant, command::Command};
+use crate::{
+    command::Command,
+    display::{DisplayVariant, InitConfig},
+    displays::generic::Controller,
+};
 use display_interface::DisplayError;
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
@@ -31,18 +35,27 @@ impl DisplayVariant for Sh1106_128_64 {
     const WIDTH: u8 = 128;
     const HEIGHT: u8 = 64;
     const COLUMN_OFFSET: u8 = 2;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1106
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        super::sh1107::init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        super::sh1107::init_column_mode_common(iface, Self::dimensions(), config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
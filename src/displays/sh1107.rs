@@ -1,13 +1,17 @@
 //! SH1107 display variants and specifics
 
-use crate::display::DisplayVariant;
+use crate::display::{DisplayVariant, InitConfig, CHARGE_PUMP_SETTLE_MS};
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
 
-use crate::command::{Command, VcomhLevel};
+use crate::{command::Command, displays::generic::Controller};
 
 /// Generic 64x128 with SH1107 controller
 #[derive(Debug, Clone, Copy)]
@@ -33,15 +37,23 @@ impl DisplayVariant for Sh1107_64_128 {
     const WIDTH: u8 = 64;
     const HEIGHT: u8 = 128;
     const COLUMN_OFFSET: u8 = 32;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1107
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions()).await?;
+        init_column_mode_common(iface, Self::dimensions(), config).await?;
         Command::ComPinConfig(true).send(iface).await?;
 
         Ok(())
@@ -71,19 +83,28 @@ pub struct Sh1107_128_128 {}
 impl DisplayVariant for Sh1107_128_128 {
     const WIDTH: u8 = 128;
     const HEIGHT: u8 = 128;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1107
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
 
@@ -100,6 +121,7 @@ impl DisplayVariant for Sh1107_128_128 {
 pub async fn init_column_mode_common<DI>(
     iface: &mut DI,
     dimensions: (u8, u8),
+    config: &InitConfig,
 ) -> Result<(), DisplayError>
 where
     DI: AsyncWriteOnlyDataCommand,
@@ -108,21 +130,55 @@ where
     // TODO: Break up into nice bits so display modes can pick whathever they need
     let (_, display_height) = dimensions;
 
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x8, 0x0).send(iface).await?;
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::Multiplex(display_height - 1),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::ChargePump(config.charge_pump),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge.0, config.precharge.1),
+            Command::VcomhDeselect(config.vcomh),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
+}
 
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
+/// Turn the panel off and switch off the charge pump, or switch the charge pump back on and
+/// wait on `delay` for it to settle before turning the panel on
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn set_sleep_common<DI, DELAY>(
+    iface: &mut DI,
+    sleep: bool,
+    config: &InitConfig,
+    delay: &mut DELAY,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DELAY: DelayNs,
+{
+    if sleep {
+        let commands = [Command::DisplayOn(false), Command::ChargePump(false)];
+        return Command::send_batch(commands, iface).await;
+    }
 
-    Ok(())
+    Command::ChargePump(config.charge_pump).send(iface).await?;
+    // the panel must only be turned on once the charge pump is running
+    if config.charge_pump {
+        delay.delay_ms(CHARGE_PUMP_SETTLE_MS).await;
+    }
+    Command::DisplayOn(true).send(iface).await
 }
@@ -1,13 +1,13 @@
 //! SH1108 display variants and specifics
 
-use crate::display::DisplayVariant;
+use crate::display::{DisplayVariant, InitConfig};
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
 
-use crate::command::{Command, VcomhLevel};
+use crate::{command::Command, displays::generic::Controller};
 
 /// Generic 64x160 with SH1108 controller
 #[maybe_async_cfg::maybe(
@@ -34,19 +34,32 @@ impl DisplayVariant for Sh1108_64_160 {
     const HEIGHT: u8 = 160;
     const COLUMN_OFFSET: u8 = 48;
     const LARGE_PAGE_ADDRESS: bool = true;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1108(0)
+    }
+
+    fn default_init_config(&self) -> InitConfig {
+        default_init_config()
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions(), 0).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 0, config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
 
@@ -75,19 +88,32 @@ impl DisplayVariant for Sh1108_96_160 {
     const HEIGHT: u8 = 160;
     const COLUMN_OFFSET: u8 = 32;
     const LARGE_PAGE_ADDRESS: bool = true;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1108(1)
+    }
+
+    fn default_init_config(&self) -> InitConfig {
+        default_init_config()
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions(), 1).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 1, config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
 /// Generic 128x160 with SH1108 controller
@@ -115,19 +141,32 @@ impl DisplayVariant for Sh1108_128_160 {
     const HEIGHT: u8 = 160;
     const COLUMN_OFFSET: u8 = 16;
     const LARGE_PAGE_ADDRESS: bool = true;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1108(2)
+    }
+
+    fn default_init_config(&self) -> InitConfig {
+        default_init_config()
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions(), 2).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 2, config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
 
@@ -156,19 +195,40 @@ impl DisplayVariant for Sh1108_160_160 {
     const HEIGHT: u8 = 160;
     const COLUMN_OFFSET: u8 = 0;
     const LARGE_PAGE_ADDRESS: bool = true;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Sh1108(3)
+    }
+
+    fn default_init_config(&self) -> InitConfig {
+        default_init_config()
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions(), 3).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
+        init_column_mode_common(iface, Self::dimensions(), 3, config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
+    }
+}
 
-        Ok(())
+/// Default initialisation parameters of the SH1108 variants
+pub fn default_init_config() -> InitConfig {
+    InitConfig {
+        clock_div: (0x6, 0x0),
+        ..InitConfig::default()
     }
 }
 
@@ -186,6 +246,7 @@ pub async fn init_column_mode_common<DI>(
     iface: &mut DI,
     dimensions: (u8, u8),
     resolution: u8,
+    config: &InitConfig,
 ) -> Result<(), DisplayError>
 where
     DI: AsyncWriteOnlyDataCommand,
@@ -194,25 +255,25 @@ where
     // TODO: Break up into nice bits so display modes can pick whathever they need
     let (_, display_height) = dimensions;
 
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x6, 0x0).send(iface).await?;
-    Command::DisplayResolution(resolution).send(iface).await?;
-    Command::PreChargePeriod(0x8, 0x2).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Ok(())
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::DisplayResolution(resolution),
+            Command::PreChargePeriod(0x8, 0x2),
+            Command::DisplayOn(true),
+            Command::Multiplex(display_height - 1),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::ChargePump(config.charge_pump),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge.0, config.precharge.1),
+            Command::VcomhDeselect(config.vcomh),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
 }
@@ -0,0 +1,169 @@
+//! SH1122 display variants and specifics
+//!
+//! 4 bit grayscale controller, use with [`GrayscaleMode`](crate::mode::GrayscaleMode).
+
+use crate::display::{GrayscaleDisplayVariant, InitConfig};
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError};
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::command::Command;
+
+/// Generic 256x64 with SH1122 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Sh1122_256_64 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl GrayscaleDisplayVariant for Sh1122_256_64 {
+    const WIDTH: u16 = 256;
+    const HEIGHT: u16 = 64;
+
+    fn default_init_config() -> InitConfig {
+        InitConfig {
+            contrast: 0x80,
+            precharge: (0x2, 0x2),
+            clock_div: (0x5, 0x0),
+            ..InitConfig::default()
+        }
+    }
+
+    async fn init<DI>(iface: &mut DI, config: &InitConfig) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_common(iface, Self::dimensions(), config).await
+    }
+
+    async fn set_sleep<DI, DELAY>(
+        iface: &mut DI,
+        sleep: bool,
+        config: &InitConfig,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        DELAY: DelayNs,
+    {
+        // same DC-DC converter commands as the SH1107
+        crate::displays::sh1107::set_sleep_common(iface, sleep, config, delay).await
+    }
+
+    async fn draw_window<DI>(
+        iface: &mut DI,
+        x: u16,
+        y: u16,
+        width: u16,
+        buf: &[u8],
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        draw_window_common(iface, x, y, width, buf).await
+    }
+}
+
+/// Initialise the display with a horizontally incrementing address, two pixels per byte.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn init_common<DI>(
+    iface: &mut DI,
+    dimensions: (u16, u16),
+    config: &InitConfig,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::Multiplex((display_height - 1) as u8),
+            Command::DisplayOffset(0),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::ChargePump(config.charge_pump),
+            Command::SegmentRemap(false),
+            Command::ReverseComDir(false),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge.0, config.precharge.1),
+            Command::VcomhDeselect(config.vcomh),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
+}
+
+/// Send a window of pixel data. The controller only auto increments the column address, so the
+/// row and column address is set again for every row.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn draw_window_common<DI>(
+    iface: &mut DI,
+    x: u16,
+    y: u16,
+    width: u16,
+    buf: &[u8],
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    if width == 0 || buf.is_empty() {
+        return Ok(());
+    }
+
+    // one column address covers two pixels
+    let col = x / 2;
+
+    for (row, data) in buf.chunks((width / 2) as usize).enumerate() {
+        Command::send_batch(
+            [
+                Command::RowAddress((y + row as u16) as u8),
+                Command::ColumnAddressLow(0xF & col as u8),
+                Command::ColumnAddressHigh(0x7 & (col >> 4) as u8),
+            ],
+            iface,
+        )
+        .await?;
+        iface.send_data(DataFormat::U8(data)).await?;
+    }
+
+    Ok(())
+}
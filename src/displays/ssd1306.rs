@@ -0,0 +1,356 @@
+//! SSD1306 display variants and specifics
+
+use crate::display::{DisplayVariant, InitConfig, CHARGE_PUMP_SETTLE_MS};
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{command::Command, displays::generic::Controller, scroll::ScrollConfig};
+
+/// Generic 128x64 with SSD1306 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_128_64 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for Ssd1306_128_64 {
+    const WIDTH: u8 = 128;
+    const HEIGHT: u8 = 64;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1306
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+        //display_rotation: DisplayRotation,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_column_mode_common(iface, Self::dimensions(), true, config).await
+    }
+}
+
+/// Generic 128x32 with SSD1306 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_128_32 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for Ssd1306_128_32 {
+    const WIDTH: u8 = 128;
+    const HEIGHT: u8 = 32;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1306
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+        //display_rotation: DisplayRotation,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_column_mode_common(iface, Self::dimensions(), false, config).await
+    }
+}
+
+/// Generic 96x16 with SSD1306 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_96_16 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for Ssd1306_96_16 {
+    const WIDTH: u8 = 96;
+    const HEIGHT: u8 = 16;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1306
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+        //display_rotation: DisplayRotation,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_column_mode_common(iface, Self::dimensions(), false, config).await
+    }
+}
+
+/// Generic 72x40 with SSD1306 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_72_40 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for Ssd1306_72_40 {
+    const WIDTH: u8 = 72;
+    const HEIGHT: u8 = 40;
+    const COLUMN_OFFSET: u8 = 28;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1306
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+        //display_rotation: DisplayRotation,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_column_mode_common(iface, Self::dimensions(), true, config).await
+    }
+}
+
+/// Generic 64x48 with SSD1306 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306_64_48 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DisplayVariant for Ssd1306_64_48 {
+    const WIDTH: u8 = 64;
+    const HEIGHT: u8 = 48;
+    const COLUMN_OFFSET: u8 = 32;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1306
+    }
+
+    async fn init_column_mode<DI>(
+        &self,
+        iface: &mut DI,
+        config: &InitConfig,
+        //display_rotation: DisplayRotation,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_column_mode_common(iface, Self::dimensions(), true, config).await
+    }
+}
+
+/// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
+/// column 0 on the left and column _(display_width - 1)_ on the right.
+///
+/// `alternative_com_pins` selects the COM pin hardware configuration: 64 and 48 row panels
+/// use the alternative layout, 32 and 16 row panels are wired sequentially.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn init_column_mode_common<DI>(
+    iface: &mut DI,
+    dimensions: (u8, u8),
+    alternative_com_pins: bool,
+    config: &InitConfig,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::Multiplex(display_height - 1),
+            Command::DisplayOffset(0),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::Ssd1306ChargePump(config.charge_pump),
+            Command::ComPinConfig(alternative_com_pins),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge.0, config.precharge.1),
+            Command::VcomhDeselect(config.vcomh),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
+}
+
+/// Turn the panel off and switch off the charge pump, or switch the charge pump back on and
+/// wait on `delay` for it to settle before turning the panel on
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn set_sleep_common<DI, DELAY>(
+    iface: &mut DI,
+    sleep: bool,
+    config: &InitConfig,
+    delay: &mut DELAY,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DELAY: DelayNs,
+{
+    if sleep {
+        let commands = [Command::DisplayOn(false), Command::Ssd1306ChargePump(false)];
+        return Command::send_batch(commands, iface).await;
+    }
+
+    Command::Ssd1306ChargePump(config.charge_pump).send(iface).await?;
+    // the panel must only be turned on once the charge pump is running
+    if config.charge_pump {
+        delay.delay_ms(CHARGE_PUMP_SETTLE_MS).await;
+    }
+    Command::DisplayOn(true).send(iface).await
+}
+
+/// Set up a continuous hardware scroll without starting it
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn setup_scroll_common<DI>(
+    iface: &mut DI,
+    config: &ScrollConfig,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    match *config {
+        ScrollConfig::Horizontal {
+            direction,
+            start_page,
+            end_page,
+            interval,
+        } => {
+            Command::HScrollSetup(direction, start_page, end_page, interval)
+                .send(iface)
+                .await
+        }
+        ScrollConfig::Diagonal {
+            direction,
+            start_page,
+            end_page,
+            interval,
+            vertical_offset,
+            fixed_rows,
+            scroll_rows,
+        } => {
+            Command::VScrollArea(fixed_rows, scroll_rows)
+                .send(iface)
+                .await?;
+            Command::VHScrollSetup(direction, start_page, end_page, interval, vertical_offset)
+                .send(iface)
+                .await
+        }
+    }
+}
@@ -1,13 +1,13 @@
 //! SSD1309 display variants and specifics
 
-use crate::display::DisplayVariant;
+use crate::display::{DisplayVariant, InitConfig};
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
 
-use crate::command::{Command, VcomhLevel};
+use crate::{command::Command, displays::generic::Controller, scroll::ScrollConfig};
 
 /// Generic 128x128 with SSD1309 controller
 #[maybe_async_cfg::maybe(
@@ -32,19 +32,28 @@ pub struct Ssd1309_128_64 {}
 impl DisplayVariant for Ssd1309_128_64 {
     const WIDTH: u8 = 128;
     const HEIGHT: u8 = 64;
+    type Buffer = [u8; Self::BUFFER_SIZE];
+    type PageBuffer = [u8; Self::WIDTH as usize];
+
+    fn controller(&self) -> Controller {
+        Controller::Ssd1309
+    }
 
     async fn init_column_mode<DI>(
+        &self,
         iface: &mut DI,
+        config: &InitConfig,
         //display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError>
     where
         DI: AsyncWriteOnlyDataCommand,
     {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), config).await?;
+        Command::send_batch(
+            [Command::DisplayOffset(0), Command::ComPinConfig(true)],
+            iface,
+        )
+        .await
     }
 }
 
@@ -61,6 +70,7 @@ impl DisplayVariant for Ssd1309_128_64 {
 pub async fn init_column_mode_common<DI>(
     iface: &mut DI,
     dimensions: (u8, u8),
+    config: &InitConfig,
 ) -> Result<(), DisplayError>
 where
     DI: AsyncWriteOnlyDataCommand,
@@ -69,21 +79,78 @@ where
     // TODO: Break up into nice bits so display modes can pick whathever they need
     let (_, display_height) = dimensions;
 
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x8, 0x0).send(iface).await?;
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::Multiplex(display_height - 1),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::ChargePump(config.charge_pump),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge.0, config.precharge.1),
+            Command::VcomhDeselect(config.vcomh),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
+}
 
-    Ok(())
+/// Set up a continuous hardware scroll without starting it. All `width` columns
+/// are scrolled, as on the SSD1306.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn setup_scroll_common<DI>(
+    iface: &mut DI,
+    config: &ScrollConfig,
+    width: u8,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    match *config {
+        ScrollConfig::Horizontal {
+            direction,
+            start_page,
+            end_page,
+            interval,
+        } => {
+            Command::Ssd1309HScrollSetup(direction, start_page, end_page, interval, 0, width - 1)
+                .send(iface)
+                .await
+        }
+        ScrollConfig::Diagonal {
+            direction,
+            start_page,
+            end_page,
+            interval,
+            vertical_offset,
+            fixed_rows,
+            scroll_rows,
+        } => {
+            Command::VScrollArea(fixed_rows, scroll_rows)
+                .send(iface)
+                .await?;
+            Command::Ssd1309VHScrollSetup(
+                direction,
+                start_page,
+                end_page,
+                interval,
+                vertical_offset,
+                0,
+                width - 1,
+            )
+            .send(iface)
+            .await
+        }
+    }
 }
@@ -0,0 +1,176 @@
+//! SSD1327 display variants and specifics
+//!
+//! 4 bit grayscale controller, use with [`GrayscaleMode`](crate::mode::GrayscaleMode).
+
+use crate::display::{GrayscaleDisplayVariant, InitConfig, VcomhLevel};
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError};
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::command::Command;
+
+/// Generic 128x128 with SSD1327 controller
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1327_128_128 {}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl GrayscaleDisplayVariant for Ssd1327_128_128 {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 128;
+
+    fn default_init_config() -> InitConfig {
+        InitConfig {
+            contrast: 0x80,
+            precharge: (0x1, 0x5),
+            clock_div: (0x0, 0x1),
+            ..InitConfig::default()
+        }
+    }
+
+    async fn init<DI>(iface: &mut DI, config: &InitConfig) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        init_common(iface, Self::dimensions(), config).await
+    }
+
+    async fn set_sleep<DI, DELAY>(
+        iface: &mut DI,
+        sleep: bool,
+        _config: &InitConfig,
+        _delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+        DELAY: DelayNs,
+    {
+        // the panel supply is external, there is no charge pump to switch
+        Command::DisplayOn(!sleep).send(iface).await
+    }
+
+    async fn draw_window<DI>(
+        iface: &mut DI,
+        x: u16,
+        y: u16,
+        width: u16,
+        buf: &[u8],
+    ) -> Result<(), DisplayError>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        draw_window_common(iface, x, y, width, buf).await
+    }
+}
+
+/// Initialise the display with a horizontally incrementing address, two pixels per byte.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn init_common<DI>(
+    iface: &mut DI,
+    dimensions: (u16, u16),
+    config: &InitConfig,
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+
+    Command::send_batch(
+        [
+            Command::DisplayOn(false),
+            // Column address remap, COM remap and split odd/even COM lines. With this layout the
+            // left pixel of each pair is stored in the high nibble.
+            Command::Ssd1327Remap(0x51),
+            Command::Ssd1327StartLine(0),
+            Command::Ssd1327DisplayOffset(0),
+            Command::Multiplex((display_height - 1) as u8),
+            Command::Ssd1327ClockDiv(config.clock_div.0, config.clock_div.1),
+            Command::Contrast(config.contrast),
+            Command::Ssd1327PhaseLength(config.precharge.0, config.precharge.1),
+            // not covered by InitConfig
+            Command::Ssd1327PreChargeVoltage(0x8),
+            Command::Ssd1327SecondPreCharge(0x1),
+            Command::Ssd1327Vcomh(vcomh_level(config.vcomh)),
+            // Normal display mode on this controller
+            Command::AllOn(false),
+            Command::DisplayOn(true),
+        ],
+        iface,
+    )
+    .await
+}
+
+/// Get the VCOMH level of the controller nearest to `level`, out of 0.72, 0.82 and 0.86 * Vcc
+fn vcomh_level(level: VcomhLevel) -> u8 {
+    match level {
+        VcomhLevel::V065 => 0x0,
+        VcomhLevel::V077 | VcomhLevel::V083 => 0x5,
+        VcomhLevel::Auto => 0x7,
+    }
+}
+
+/// Send a window of pixel data. The column and row window is set once and the controller wraps
+/// to the next row of the window by itself.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub async fn draw_window_common<DI>(
+    iface: &mut DI,
+    x: u16,
+    y: u16,
+    width: u16,
+    buf: &[u8],
+) -> Result<(), DisplayError>
+where
+    DI: AsyncWriteOnlyDataCommand,
+{
+    if width == 0 || buf.is_empty() {
+        return Ok(());
+    }
+
+    let row_bytes = width / 2;
+    let rows = (buf.len() as u16).div_ceil(row_bytes);
+
+    Command::send_batch(
+        [
+            Command::ColumnAddressRange((x / 2) as u8, (x / 2 + row_bytes - 1) as u8),
+            Command::RowAddressRange(y as u8, (y + rows - 1) as u8),
+        ],
+        iface,
+    )
+    .await?;
+
+    iface.send_data(DataFormat::U8(buf)).await
+}
@@ -5,11 +5,10 @@
 //! * display_interface_spi::SPIInterface<...> or
 //! * display_interface_i2c::I2CInterface<...>
 //!
-//! This is provided to the [`Builder`](builder/struct.Builder.html),
-//! which will in turn create a driver instance in a particular mode. By default, the builder
-//! returns a `mode::RawMode` instance which isn't very useful by itself. You can coerce the driver
-//! into a more useful mode by calling `into()` and defining the type you want to coerce to. For
-//! example, to initialise the display with an I2C interface and
+//! This is provided to the [`Builder`](builder/struct.Builder.html), which returns an
+//! [`UninitDisplay`]. Nothing can be drawn until it has been reset and initialised: `init()`
+//! consumes it and returns a driver instance in a particular mode, chosen by the type you assign
+//! it to. For example, to initialise the display with
 //! [`mode::GraphicsMode`](mode/graphics/struct.GraphicsMode.html), you would do something like
 //! this:
 //!
@@ -17,21 +16,13 @@
 //! use oled_async::{prelude::*, Builder};
 //! use oled_async::displays::sh1107::Sh1107_128_128;
 //!
-//! let raw_disp = Builder::new(Sh1107_128_128 {})
+//! let uninit = Builder::new(Sh1107_128_128 {})
 //!     .with_rotation(crate::DisplayRotation::Rotate180)
 //!     .connect(display_interface);
-//! let mut display: GraphicsMode<_, _> = raw_disp.into();
-//! display.reset(&mut reset, &mut delay).unwrap();
-//! display.init().await.unwrap();
+//! let mut display: GraphicsMode<_, _> = uninit.init(&mut reset, &mut delay).await.unwrap();
 //! display.clear();
 //! display.flush().await.unwrap();
 //!
-//!
-//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c(i2c).into();
-//!
-//! display.init().unwrap();
-//! display.flush().unwrap();
-//!
 //! display.set_pixel(10, 20, 1);
 //!
 //! display.flush().await.unwrap();
@@ -64,10 +55,11 @@
 //! let mut display: GraphicsMode<_, _> = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
 //!         .with_rotation(crate::DisplayRotation::Rotate180)
 //!         .connect(display_interface)
-//!         .into();
+//!         .init(&mut reset, &mut delay)
+//!         .await
+//!         .unwrap();
 //!
-//! display.init().unwrap();
-//! display.flush().unwrap();
+//! display.flush().await.unwrap();
 //!
 //! let text_style = MonoTextStyleBuilder::new()
 //!     .font(&FONT_6X10)
@@ -105,15 +97,38 @@ pub enum Error<CommE, PinE> {
 }
 
 extern crate embedded_hal as hal;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod macros;
 
+pub mod bitmap;
+pub mod brightness;
 pub mod builder;
+pub mod burnin;
 mod command;
 pub mod display;
 pub mod displayrotation;
 pub mod displays;
+mod font;
 pub mod mode;
+pub mod power;
 pub mod prelude;
 pub mod properties;
+#[cfg(feature = "std")]
+pub mod screenshot;
+pub mod scroll;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(all(test, feature = "std"))]
+mod test_helpers;
+pub use crate::builder::{Builder, NoOutputPin, UninitDisplay};
+
 #[doc(hidden)]
-//pub mod test_helpers;
-pub use crate::builder::{Builder, NoOutputPin};
+pub mod __private {
+    #[cfg(not(feature = "blocking"))]
+    pub use display_interface::AsyncWriteOnlyDataCommand;
+    pub use display_interface::DisplayError;
+    #[cfg(feature = "blocking")]
+    pub use display_interface::WriteOnlyDataCommand;
+}
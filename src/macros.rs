@@ -0,0 +1,188 @@
+//! Macros for defining display variants out of tree
+
+/// Define a display variant for a panel built on one of the supported controllers
+///
+/// Emits a unit struct and a [`DisplayVariant`](crate::display::DisplayVariant) implementation
+/// for it, in either the async or the `blocking` flavour the crate was built with. The controller
+/// family is one of the [`Controller`](crate::displays::generic::Controller) variants and picks
+/// the initialisation sequence.
+///
+/// `column_offset`, `large_page_address` (defaults to `true` for SH1108, `false` otherwise),
+/// `display_offset`, `alternative_com_pins` and `init_config` are optional, but must keep the
+/// order shown below. Every field is followed by a comma. `init_config` replaces the
+/// [`InitConfig`](crate::display::InitConfig) defaults of the controller family.
+///
+/// ```rust,no_run
+/// oled_async::define_variant! {
+///     /// 72x40 module with SSD1306 controller
+///     pub struct MyPanel {
+///         controller: Ssd1306,
+///         width: 72,
+///         height: 40,
+///         column_offset: 28,
+///         alternative_com_pins: true,
+///     }
+/// }
+///
+/// oled_async::define_variant! {
+///     /// 96x160 module with SH1108 controller
+///     pub struct MyTallPanel {
+///         controller: Sh1108(1),
+///         width: 96,
+///         height: 160,
+///         column_offset: 32,
+///         display_offset: 0,
+///         init_config: oled_async::display::InitConfig {
+///             contrast: 0x40,
+///             ..oled_async::displays::sh1108::default_init_config()
+///         },
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_variant {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            controller: $family:ident $(($resolution:expr))?,
+            width: $width:expr,
+            height: $height:expr,
+            $(column_offset: $column_offset:expr,)?
+            $(large_page_address: $large_page_address:expr,)?
+            $(display_offset: $display_offset:expr,)?
+            $(alternative_com_pins: $alternative_com_pins:expr,)?
+            $(init_config: $init_config:expr,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        $vis struct $name {}
+
+        impl $crate::display::DisplayVariant for $name {
+            const WIDTH: u8 = $width;
+            const HEIGHT: u8 = $height;
+            const COLUMN_OFFSET: u8 = $crate::define_variant!(@or 0 $(, $column_offset)?);
+            const LARGE_PAGE_ADDRESS: bool = $crate::define_variant!(
+                @or matches!(
+                    $crate::displays::generic::Controller::$family $(($resolution))?,
+                    $crate::displays::generic::Controller::Sh1108(_)
+                )
+                $(, $large_page_address)?
+            );
+            type Buffer = [u8; <Self as $crate::display::DisplayVariant>::BUFFER_SIZE];
+            type PageBuffer = [u8; <Self as $crate::display::DisplayVariant>::WIDTH as usize];
+
+            fn controller(&self) -> $crate::displays::generic::Controller {
+                $crate::displays::generic::Controller::$family $(($resolution))?
+            }
+
+            $crate::__variant_init_column_mode!(
+                $crate::displays::generic::RuntimeVariant::new(
+                    $crate::displays::generic::Controller::$family $(($resolution))?,
+                    $width,
+                    $height,
+                )
+                .with_display_offset($crate::define_variant!(@or 0 $(, $display_offset)?))
+                .with_alternative_com_pins(
+                    $crate::define_variant!(@or true $(, $alternative_com_pins)?)
+                )
+                $(, $init_config)?
+            );
+        }
+    };
+    (@or $default:expr) => {
+        $default
+    };
+    (@or $default:expr, $value:expr) => {
+        $value
+    };
+}
+
+#[cfg(not(feature = "blocking"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __variant_init_column_mode {
+    ($variant:expr $(, $init_config:expr)?) => {
+        fn default_init_config(&self) -> $crate::display::InitConfig {
+            $crate::define_variant!(
+                @or $crate::display::DisplayVariant::default_init_config(&$variant)
+                $(, $init_config)?
+            )
+        }
+
+        async fn init_column_mode<DI>(
+            &self,
+            iface: &mut DI,
+            config: &$crate::display::InitConfig,
+        ) -> Result<(), $crate::__private::DisplayError>
+        where
+            DI: $crate::__private::AsyncWriteOnlyDataCommand,
+        {
+            $crate::display::DisplayVariant::init_column_mode(&$variant, iface, config).await
+        }
+    };
+}
+
+#[cfg(feature = "blocking")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __variant_init_column_mode {
+    ($variant:expr $(, $init_config:expr)?) => {
+        fn default_init_config(&self) -> $crate::display::InitConfig {
+            $crate::define_variant!(
+                @or $crate::display::DisplayVariant::default_init_config(&$variant)
+                $(, $init_config)?
+            )
+        }
+
+        fn init_column_mode<DI>(
+            &self,
+            iface: &mut DI,
+            config: &$crate::display::InitConfig,
+        ) -> Result<(), $crate::__private::DisplayError>
+        where
+            DI: $crate::__private::WriteOnlyDataCommand,
+        {
+            $crate::display::DisplayVariant::init_column_mode(&$variant, iface, config)
+        }
+    };
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::{display::InitConfig, mode::GraphicsMode, sim::block_on, sim::Emulator, Builder};
+
+    crate::define_variant! {
+        /// 72x40 module, the visible area is in the middle of display RAM
+        struct TestPanel {
+            controller: Ssd1306,
+            width: 72,
+            height: 40,
+            column_offset: 28,
+            alternative_com_pins: true,
+            init_config: InitConfig {
+                contrast: 0x42,
+                ..InitConfig::default()
+            },
+        }
+    }
+
+    #[test]
+    fn defined_variant_drives_emulator() {
+        let variant = TestPanel {};
+        let mut emulator = Emulator::new(&variant);
+        let raw = Builder::new(variant).connect(&mut emulator);
+        let mut display: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(71, 39, 1);
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(emulator.size(), (72, 40));
+        assert_eq!(emulator.contrast(), 0x42);
+        assert!(emulator.display_on() && emulator.charge_pump());
+        assert!(emulator.pixel(0, 0) && emulator.pixel(71, 39));
+        let lit: u32 = emulator.gddram().iter().map(|byte| byte.count_ones()).sum();
+        assert_eq!(lit, 2);
+    }
+}
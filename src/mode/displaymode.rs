@@ -2,21 +2,51 @@
 
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
 
 use crate::properties::DisplayProperties;
 
+/// Construction of display modes, kept out of reach of other crates so that a display mode can
+/// only be created initialised, by [`UninitDisplay::init`](crate::builder::UninitDisplay::init)
+pub(crate) mod sealed {
+    use crate::properties::DisplayProperties;
+
+    /// Display mode that can be created from the display properties
+    pub trait NewMode<DV, DI> {
+        /// Allocate all required data for the mode, without sending anything to the display
+        fn new(properties: DisplayProperties<DV, DI>) -> Self;
+    }
+}
+
 /// Display mode abstraction
+///
+/// Switching modes with [`DisplayMode::into`] hands over the display as it is, without
+/// initialising it again.
 pub struct DisplayMode<MODE>(pub MODE);
 
 /// Trait with core functionality for display mode switching
-pub trait DisplayModeTrait<DV, DI> {
-    /// Allocate all required data and initialise display for mode
-    fn new(properties: DisplayProperties<DV, DI>) -> Self;
-
+///
+/// The trait is sealed, display modes are created by
+/// [`UninitDisplay::init`](crate::builder::UninitDisplay::init).
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub trait DisplayModeTrait<DV, DI>: sealed::NewMode<DV, DI> {
     /// Release resources for reuse with different mode
     fn release(self) -> DisplayProperties<DV, DI>;
+
+    /// Send the initialisation sequence for this mode to the display. Called by
+    /// [`UninitDisplay::init`](crate::builder::UninitDisplay::init), and again to recover a
+    /// display that lost power.
+    #[allow(async_fn_in_trait)]
+    async fn init(&mut self) -> Result<(), DisplayError>;
 }
 
 #[maybe_async_cfg::maybe(
@@ -28,21 +58,12 @@ pub trait DisplayModeTrait<DV, DI> {
     async(not(feature = "blocking"), keep_self)
 )]
 impl<MODE> DisplayMode<MODE> {
-    /// Setup display to run in requested mode
-    pub fn new<DV, DI>(properties: DisplayProperties<DV, DI>) -> Self
-    where
-        DI: AsyncWriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DV, DI>,
-    {
-        DisplayMode(MODE::new(properties))
-    }
-
-    /// Change into any mode implementing DisplayModeTrait
+    /// Change into any mode implementing DisplayModeTrait. The display is handed over as it is,
+    /// without being initialised again for the new mode.
     // TODO: Figure out how to stay as generic DisplayMode but act as particular mode
     pub fn into<DV, DI, NMODE: DisplayModeTrait<DV, DI>>(self) -> NMODE
     where
         DI: AsyncWriteOnlyDataCommand,
-        DV: crate::display::DisplayVariant,
         MODE: DisplayModeTrait<DV, DI>,
     {
         let properties = self.0.release();
@@ -12,10 +12,10 @@
 //!     let mut disp: GraphicsMode<_, _> = Builder::new(Display {})
 //!         .with_rotation(crate::DisplayRotation::Rotate180)
 //!         .connect(display_interface)
-//!         .into();
+//!         .init(&mut reset, &mut delay)
+//!         .await
+//!         .unwrap();
 //!
-//!     disp.reset(&mut reset, &mut delay).unwrap();
-//!     disp.init().await.unwrap();
 //!     disp.clear();
 //!     disp.flush().await.unwrap();
 //!
@@ -37,16 +37,48 @@ use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::WriteOnlyDataCommand;
 
 use display_interface::DisplayError;
-use hal::{delay::DelayNs, digital::OutputPin};
+use core::ops::Range;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
 
 use crate::{
-    display, displayrotation::DisplayRotation, mode::displaymode::DisplayModeTrait,
+    bitmap::Bitmap,
+    brightness,
+    burnin::{BurnIn, BurnInConfig, IdleState},
+    display,
+    displayrotation::{DisplayRotation, Mirror},
+    mode::displaymode::{sealed::NewMode, DisplayModeTrait},
+    power::{PowerControl, PowerState},
     properties::DisplayProperties,
+    scroll::ScrollConfig,
 };
+#[cfg(feature = "std")]
+use crate::screenshot::Frame;
 
-const DEFAULT_BUFFER_SIZE: usize = 160 * 160 / 8;
+/// Enough pages for any display height
+const MAX_PAGES: usize = 256 / 8;
+/// Column span of a page with nothing to send
+const CLEAN: (u8, u8) = (u8::MAX, 0);
+/// Time between contrast changes of a fade, in milliseconds
+const FADE_STEP_MS: u32 = 10;
+/// Unchanged columns sent rather than starting a new transfer, which costs three command bytes
+const MAX_GAP: usize = 3;
 
 /// Graphics mode handler
+///
+/// `B` is the storage of the buffer. By default it's the array sized for the display variant,
+/// [`DisplayVariant::Buffer`](display::DisplayVariant::Buffer). Another array can be picked as
+/// `GraphicsMode<_, _, [u8; 1024]>`, which fails to compile if it's smaller than
+/// [`DisplayVariant::BUFFER_SIZE`](display::DisplayVariant::BUFFER_SIZE).
+/// [`GraphicsMode::with_buffer`] uses external storage instead, e.g. a `&'static mut [u8]` placed
+/// in DMA capable RAM.
+///
+/// Setting the shadow buffer size `SB` to the size of the buffer keeps a copy of what was last
+/// sent to the display, and `flush()` then only sends the bytes that really changed. This catches
+/// redraws of identical pixels, at the cost of twice the memory. The default of `0` disables the
+/// shadow buffer.
 #[maybe_async_cfg::maybe(
     sync(
         feature = "blocking",
@@ -55,15 +87,29 @@ const DEFAULT_BUFFER_SIZE: usize = 160 * 160 / 8;
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-pub struct GraphicsMode<DV, DI, const BS: usize = DEFAULT_BUFFER_SIZE>
+pub struct GraphicsMode<DV, DI, B = <DV as display::DisplayVariant>::Buffer, const SB: usize = 0>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
 {
     properties: DisplayProperties<DV, DI>,
-    buffer: [u8; BS],
-    top_left: (u8, u8),
-    bot_right: (u8, u8),
+    buffer: B,
+    /// First and last modified column of each page since the last flush
+    dirty: [(u8, u8); MAX_PAGES],
+    /// Contents of display RAM, for pages not in `shadow_stale`
+    shadow: [u8; SB],
+    /// Bit mask of the pages whose shadow doesn't match display RAM
+    shadow_stale: u32,
+    start_line: u8,
+    /// Columns and rows the image is moved by for burn-in protection
+    shift: (u8, u8),
+    /// Contrast set with `set_contrast`, shown unless dimmed
+    contrast: u8,
+    /// Brightness level of `contrast`
+    brightness: u8,
+    reflush_on_wake: bool,
+    burn_in: Option<BurnIn>,
 }
 
 #[maybe_async_cfg::maybe(
@@ -74,25 +120,55 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV, DI, const BS: usize> DisplayModeTrait<DV, DI> for GraphicsMode<DV, DI, BS>
+impl<DV, DI, const BS: usize, const SB: usize> NewMode<DV, DI>
+    for GraphicsMode<DV, DI, [u8; BS], SB>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
 {
     /// Create new GraphicsMode instance
     fn new(properties: DisplayProperties<DV, DI>) -> Self {
+        let () = Self::BUFFER_SIZE_CHECK;
+        let contrast = properties.get_init_config().contrast;
+
         GraphicsMode {
             properties,
             buffer: [0u8; BS],
-            top_left: (DV::WIDTH, DV::HEIGHT),
-            bot_right: (0, 0),
+            dirty: [CLEAN; MAX_PAGES],
+            shadow: [0u8; SB],
+            shadow_stale: u32::MAX,
+            start_line: 0,
+            shift: (0, 0),
+            contrast,
+            brightness: brightness::from_contrast(contrast),
+            reflush_on_wake: false,
+            burn_in: None,
         }
     }
+}
 
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize, const SB: usize> DisplayModeTrait<DV, DI>
+    for GraphicsMode<DV, DI, [u8; BS], SB>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
     /// Release all resources used by GraphicsMode
     fn release(self) -> DisplayProperties<DV, DI> {
         self.properties
     }
+
+    async fn init(&mut self) -> Result<(), DisplayError> {
+        GraphicsMode::init(self).await
+    }
 }
 
 #[maybe_async_cfg::maybe(
@@ -103,100 +179,308 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV, DI, const BS: usize> GraphicsMode<DV, DI, BS>
+impl<DV, DI, B, const SB: usize> PowerControl for GraphicsMode<DV, DI, B, SB>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
 {
-    /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
-    pub fn clear(&mut self) {
-        self.buffer = [0; BS];
-        self.top_left = (0, 0);
-        self.bot_right = (DV::WIDTH - 1, DV::HEIGHT - 1);
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        GraphicsMode::display_on(self, on).await
     }
 
-    /// Reset display
-    pub fn reset<RST, DELAY, PinE>(&mut self, rst: &mut RST, delay: &mut DELAY) -> Result<(), PinE>
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
     where
-        RST: OutputPin<Error = PinE>,
         DELAY: DelayNs,
     {
-        rst.set_high()?;
-        delay.delay_ms(1);
-        rst.set_low()?;
-        delay.delay_ms(10);
-        rst.set_high()
+        let state = if sleep { PowerState::Sleep } else { PowerState::On };
+        self.set_power_state(state, delay).await
     }
+}
 
-    /// Write out data to display
-    pub async fn flush(&mut self) -> Result<(), DisplayError> {
-        // nothing drawn since last flush
-        if self.top_left.0 > self.bot_right.0 || self.top_left.1 > self.bot_right.1 {
-            return Ok(());
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize, const SB: usize> GraphicsMode<DV, DI, [u8; BS], SB>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Fails to compile when a display mode is created with buffers too small for the display
+    const BUFFER_SIZE_CHECK: () = {
+        assert!(BS >= DV::BUFFER_SIZE, "buffer is smaller than DisplayVariant::BUFFER_SIZE");
+        assert!(SB == 0 || SB >= BS, "shadow buffer is smaller than the buffer");
+    };
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, B, const SB: usize> GraphicsMode<DV, DI, B, SB>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Switch the initialised display `mode` to graphics mode, drawing into `buffer` instead of
+    /// an array owned by `GraphicsMode`. Returns `OutOfBoundsError` if the buffer is too small for
+    /// the display, or if a shadow buffer is enabled that is smaller than the buffer.
+    ///
+    /// ```rust,no_run
+    /// static BUFFER: StaticCell<[u8; 1024]> = StaticCell::new();
+    ///
+    /// let raw: RawMode<_, _> = Builder::new(variant).connect(di).init_without_reset().await?;
+    /// let buffer = &mut BUFFER.init([0; 1024])[..];
+    /// let mut display = GraphicsMode::with_buffer(raw, buffer)?;
+    /// ```
+    pub fn with_buffer<MODE>(mode: MODE, buffer: B) -> Result<Self, DisplayError>
+    where
+        MODE: DisplayModeTrait<DV, DI>,
+    {
+        let properties = mode.release();
+        let (width, height) = properties.get_size();
+        let len = buffer.as_ref().len();
+        if len < width as usize * height.div_ceil(8) as usize || (SB != 0 && SB < len) {
+            return Err(DisplayError::OutOfBoundsError);
         }
+        let contrast = properties.get_init_config().contrast;
 
-        let base_col = self.top_left.0;
-        let end_col = self.bot_right.0;
+        Ok(GraphicsMode {
+            properties,
+            buffer,
+            dirty: [CLEAN; MAX_PAGES],
+            shadow: [0u8; SB],
+            shadow_stale: u32::MAX,
+            start_line: 0,
+            shift: (0, 0),
+            contrast,
+            brightness: brightness::from_contrast(contrast),
+            reflush_on_wake: false,
+            burn_in: None,
+        })
+    }
 
-        let base_page = self.top_left.1 / 8;
-        let end_page = self.bot_right.1.div_ceil(8);
+    /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.buffer.as_mut().fill(0);
+        self.mark_all_dirty();
+    }
 
-        // for each page in the modified area
-        for (page_num, buf) in self
-            .buffer
-            .chunks_exact(DV::WIDTH as usize)
-            .enumerate()
-            .skip(base_page as usize)
-            .take((end_page - base_page) as usize)
-        {
-            // crop to columns in the modified area
-            let buf = &buf[(base_col as usize)..=(end_col as usize)];
-            // column offsetting done in draw_page
-            self.properties
-                .draw_page(page_num as u8, base_col, buf)
-                .await?;
+    /// Write out data to display. Only the modified columns of each page are sent, so drawing in
+    /// opposite corners of the display doesn't send everything in between.
+    ///
+    /// With a shadow buffer, modified columns that still match what the display shows are
+    /// skipped too. The first flush after `init()` sends the whole buffer to fill the shadow.
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        self.flush_step(usize::MAX).await?;
+
+        Ok(())
+    }
+
+    /// Write out at most `max_bytes` of the modified columns to the display, and return whether
+    /// everything has been sent. At least one byte is sent per call.
+    ///
+    /// This splits a `flush()` into short bus transactions, so that other users of a shared bus
+    /// can get in between. Pass the display width to send about a page per call:
+    ///
+    /// ```rust,no_run
+    /// while !display.flush_step(128).await? {
+    ///     yield_now().await;
+    /// }
+    /// ```
+    ///
+    /// Drawing between steps is fine, newly modified columns are sent by a later step.
+    pub async fn flush_step(&mut self, max_bytes: usize) -> Result<bool, DisplayError> {
+        // display RAM is lost, the whole buffer is sent when the display wakes up
+        if self.properties.get_power_state() == PowerState::PowerDown {
+            return Ok(true);
         }
+        let (width, height) = self.properties.get_size();
+        let width = width as usize;
+        let shift = self.shift.0 as usize;
+        let mut budget = max_bytes.max(1);
 
-        self.top_left = (DV::WIDTH - 1, DV::HEIGHT);
-        self.bot_right = (0, 0);
+        for page in 0..height.div_ceil(8) as usize {
+            let (first, last) = self.dirty[page];
+            // nothing drawn on this page since last flush
+            if first > last {
+                continue;
+            }
+            let diff = SB != 0 && self.shadow_stale & (1 << page) == 0;
+            // display RAM left of the shifted image is only known to be blank if it's in sync
+            if first == 0 && !diff {
+                Self::blank_columns(&mut self.properties, page as u8, 0..shift.min(width)).await?;
+            }
 
-        Ok(())
+            let buf = &self.buffer.as_ref()[page * width..][..width];
+            let shadow = self
+                .shadow
+                .get_mut(page * width..(page + 1) * width)
+                .unwrap_or_default();
+
+            let (mut first, last) = (first as usize, last as usize);
+            while first <= last {
+                let (start, end) = if diff {
+                    let Some(run) = changed_runs(buf, shadow, first, last).next() else {
+                        break;
+                    };
+                    run
+                } else {
+                    (first, last)
+                };
+                if budget == 0 {
+                    self.dirty[page].0 = start as u8;
+                    return Ok(false);
+                }
+
+                let end = end.min(start.saturating_add(budget - 1));
+                // column offsetting done in draw_page, columns shifted past the right edge are
+                // not shown
+                if start + shift < width {
+                    let visible = &buf[start..=end.min(width - 1 - shift)];
+                    self.properties
+                        .draw_page(page as u8, (start + shift) as u8, visible)
+                        .await?;
+                }
+                if let Some(shadow) = shadow.get_mut(start..=end) {
+                    shadow.copy_from_slice(&buf[start..=end]);
+                }
+
+                budget -= end - start + 1;
+                first = end + 1;
+            }
+
+            self.dirty[page] = CLEAN;
+            self.shadow_stale &= !(1 << page);
+        }
+
+        Ok(true)
     }
 
     /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
     /// coordinates are out of the bounds of the display, this method call is a noop.
     pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
-        let (display_width, _) = DV::dimensions();
+        let (display_width, display_height) = self.properties.get_size();
         let display_rotation = self.properties.get_rotation();
 
         let (x, y) = match display_rotation {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
             DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
         };
-        self.top_left.0 = self.top_left.0.min(x as u8);
-        self.top_left.1 = self.top_left.1.min(y as u8);
+        if x >= display_width as u32 || y >= display_height as u32 {
+            return;
+        }
+        // map into the ring buffer set up by `set_scroll_offset`
+        let y = (y + self.start_line as u32) % display_height as u32;
 
-        self.bot_right.0 = self.bot_right.0.max(x as u8);
-        self.bot_right.1 = self.bot_right.1.max(y as u8);
+        self.mark_dirty(y / 8, y / 8, x as u8, x as u8);
 
         let idx = (y as usize / 8) * display_width as usize + x as usize;
 
-        if idx >= self.buffer.len() {
+        let Some(byte) = self.buffer.as_mut().get_mut(idx) else {
             return;
-        }
+        };
         let bit_index = y % 8;
         let bit = 1 << bit_index;
 
         if value == 0 {
-            self.buffer[idx] &= !bit;
+            *byte &= !bit;
         } else {
-            self.buffer[idx] |= bit;
+            *byte |= bit;
+        }
+    }
+
+    /// Get whether a pixel is on in the buffer, using the same coordinates as `set_pixel`.
+    /// Pixels outside of the display are off.
+    pub fn get_pixel(&self, x: u32, y: u32) -> bool {
+        let (display_width, display_height) = self.properties.get_size();
+
+        let (x, y) = match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+        };
+        if x >= display_width as u32 || y >= display_height as u32 {
+            return false;
+        }
+        let y = (y + self.start_line as u32) % display_height as u32;
+
+        let idx = (y as usize / 8) * display_width as usize + x as usize;
+        self.buffer
+            .as_ref()
+            .get(idx)
+            .is_some_and(|byte| byte & (1 << (y % 8)) != 0)
+    }
+
+    /// Get the buffer as an image, the way the user sees it with the current rotation
+    #[cfg(feature = "std")]
+    pub fn frame(&self) -> Frame {
+        let (width, height) = self.get_dimensions();
+
+        Frame::from_fn(width.into(), height.into(), |x, y| self.get_pixel(x, y))
+    }
+
+    /// Copy `bitmap` into the buffer with its top left corner at `x`, `y`, using the same
+    /// coordinates as `set_pixel`. Lit pixels of the bitmap turn pixels on, the others off. Parts
+    /// outside of the display are clipped.
+    ///
+    /// The buffer is written a byte at a time, shifting the bitmap into place, which is much
+    /// faster than drawing it pixel by pixel.
+    pub fn blit(&mut self, x: i32, y: i32, bitmap: &Bitmap<'_>) {
+        let (width, height) = self.get_dimensions();
+        let (bitmap_width, bitmap_height) = bitmap.size();
+
+        // clip to the display
+        let columns = x.max(0)..x.saturating_add_unsigned(bitmap_width).min(width as i32);
+        let rows = y.max(0)..y.saturating_add_unsigned(bitmap_height).min(height as i32);
+        if columns.is_empty() || rows.is_empty() {
+            return;
+        }
+        let columns = columns.start as u32..columns.end as u32;
+        let rows = rows.start as u32..rows.end as u32;
+
+        match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                self.copy_bytes(columns, rows, |col, row| {
+                    bitmap.column_bits(col as i32 - x, row - y)
+                })
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                self.copy_bytes(rows, columns, |col, row| {
+                    bitmap.row_bits(row - x, col as i32 - y)
+                })
+            }
         }
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
-    /// column 0 on the left, to column _n_ on the right
+    /// column 0 on the left, to column _n_ on the right. This is done by
+    /// [`UninitDisplay::init`](crate::builder::UninitDisplay::init), call it again to set up a
+    /// display that lost power.
     pub async fn init(&mut self) -> Result<(), DisplayError> {
+        if SB != 0 || self.shift.0 != 0 {
+            self.invalidate_shadow();
+        }
+        self.start_line = 0;
+        self.shift = (0, 0);
+        self.contrast = self.properties.get_init_config().contrast;
+        self.brightness = brightness::from_contrast(self.contrast);
+        if let Some(burn_in) = &mut self.burn_in {
+            burn_in.state = IdleState::Active;
+        }
         self.properties.init_column_mode().await
     }
 
@@ -215,29 +499,536 @@ where
         self.properties.set_rotation(rot).await
     }
 
+    /// Get the display mirroring
+    pub fn get_mirror(&self) -> Mirror {
+        self.properties.get_mirror()
+    }
+
+    /// Set the display mirroring, applied on top of the rotation. Mirroring is done by the
+    /// controller, so the buffer keeps the coordinates of the unmirrored image and is sent again
+    /// on the next flush, as some controllers only remap data written after the change.
+    pub async fn set_mirror(&mut self, mirror: Mirror) -> Result<(), DisplayError> {
+        self.invalidate_shadow();
+
+        self.properties.set_mirror(mirror);
+        self.properties
+            .set_rotation(self.properties.get_rotation())
+            .await
+    }
+
     /// Turn the display on or off. The display can be drawn to and retains all
     /// of its memory even while off.
     pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
         self.properties.display_on(on).await
     }
 
-    /// Set the display contrast
+    /// Set the display contrast. While the display is dimmed by the burn-in protection, it's
+    /// shown once the display is active again.
     pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
-        self.properties.set_contrast(contrast).await
+        self.contrast = contrast;
+        self.brightness = brightness::from_contrast(contrast);
+        self.properties.set_contrast(self.shown_contrast()).await
+    }
+
+    /// Set the perceptual brightness `level`, from `0` for the dimmest to `255` for the
+    /// brightest display. See the [`brightness`] module.
+    pub async fn set_brightness(&mut self, level: u8) -> Result<(), DisplayError> {
+        self.set_contrast(brightness::to_contrast(level)).await?;
+        self.brightness = level;
+
+        Ok(())
+    }
+
+    /// Get the brightness level set with `set_brightness`, or the lowest level showing the
+    /// contrast set with `set_contrast`
+    pub fn get_brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Fade from the current brightness to `level` over `duration` milliseconds, e.g. for power
+    /// on and off transitions or to follow the ambient light.
+    ///
+    /// The brightness changes in even steps of perceptual level, a step every 10 ms at most, and
+    /// `delay` waits in between:
+    ///
+    /// ```rust,no_run
+    /// display.set_brightness(0).await?;
+    /// display.flush().await?;
+    /// display.fade_to(255, 500, &mut delay).await?;
+    /// ```
+    pub async fn fade_to<DELAY>(
+        &mut self,
+        level: u8,
+        duration: u32,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let from = self.brightness as i32;
+        let distance = level as i32 - from;
+        let steps = (duration / FADE_STEP_MS).clamp(1, distance.unsigned_abs().max(1));
+
+        let mut elapsed = 0;
+        for step in 1..=steps {
+            // spread the rounding of the step times over the whole fade
+            let time = (duration as u64 * step as u64 / steps as u64) as u32;
+            delay.delay_ms(time - elapsed).await;
+            elapsed = time;
+
+            let level = from + distance * step as i32 / steps as i32;
+            let contrast = brightness::to_contrast(level as u8);
+            if contrast != self.contrast || step == steps {
+                self.set_contrast(contrast).await?;
+            }
+            self.brightness = level as u8;
+        }
+
+        Ok(())
+    }
+
+    /// Get the power state set with `set_power_state`
+    pub fn get_power_state(&self) -> PowerState {
+        self.properties.get_power_state()
+    }
+
+    /// Put the display to sleep, power it down or wake it up in place. Waking up waits on `delay`
+    /// for the charge pump to settle.
+    ///
+    /// Unlike [`PowerControl::sleep`], this keeps the display usable while it's off: the buffer
+    /// can be drawn to in any state, and `flush()` still writes display RAM while asleep. This is
+    /// what the burn-in screensaver needs, and what powering down needs, as the display has to be
+    /// initialised again afterwards. `flush()` sends nothing while powered down.
+    ///
+    /// Waking up from [`PowerState::PowerDown`] initialises the display again and restores the
+    /// scroll offset. The whole buffer is sent on the next `flush()`, or right away if enabled with
+    /// `set_reflush_on_wake`.
+    pub async fn set_power_state<DELAY>(
+        &mut self,
+        state: PowerState,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let ram_lost = self.properties.get_power_state() == PowerState::PowerDown;
+        self.properties.set_power_state(state, delay).await?;
+        if !ram_lost || state == PowerState::PowerDown {
+            return Ok(());
+        }
+
+        self.invalidate_shadow();
+        let start_line = self.hardware_start_line(self.start_line);
+        if start_line != 0 {
+            self.properties.set_start_line(start_line).await?;
+        }
+        if self.shift.1 != 0 {
+            self.blank_rows_below().await?;
+        }
+        self.properties.set_contrast(self.shown_contrast()).await?;
+        if self.reflush_on_wake {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the whole buffer as soon as the display wakes up from [`PowerState::PowerDown`],
+    /// instead of on the next `flush()`. Off by default.
+    pub fn set_reflush_on_wake(&mut self, reflush: bool) {
+        self.reflush_on_wake = reflush;
+    }
+
+    /// Scroll the displayed image vertically by changing the display start line. Display RAM row
+    /// `line` is shown in the first row, without sending the buffer again.
+    ///
+    /// Drawing is mapped into the ring buffer this creates, so that the origin stays in the top
+    /// left corner of the screen. A log style UI can scroll up by one row by incrementing the
+    /// offset and drawing the new bottom row, which is the only row sent on the next `flush()`.
+    ///
+    /// The offset is counted in display RAM rows, which run along the x axis when the display
+    /// is rotated by 90 or 270 degrees. This only works for displays whose height matches the
+    /// number of rows in display RAM. Others, like 128x32 SSD1306 displays, show rows of display
+    /// RAM the buffer isn't sent to, and return `OutOfBoundsError` for any offset but `0`.
+    pub async fn set_scroll_offset(&mut self, line: u8) -> Result<(), DisplayError> {
+        let height = self.properties.get_size().1;
+        let line = line % height;
+        if line != 0 && height as u16 != self.ram_rows() {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.properties
+            .set_start_line(self.hardware_start_line(line))
+            .await?;
+        self.start_line = line;
+
+        Ok(())
+    }
+
+    /// Get the scroll offset set with `set_scroll_offset`
+    pub fn get_scroll_offset(&self) -> u8 {
+        self.start_line
+    }
+
+    /// Set up and start a continuous hardware scroll. Only supported by SSD130x controllers.
+    pub async fn start_scroll(&mut self, config: ScrollConfig) -> Result<(), DisplayError> {
+        self.properties.start_scroll(config).await
+    }
+
+    /// Stop a hardware scroll. As the scrolled image is not written back to display RAM, the
+    /// whole buffer is sent again on the next `flush()`.
+    pub async fn stop_scroll(&mut self) -> Result<(), DisplayError> {
+        self.properties.stop_scroll().await?;
+
+        self.invalidate_shadow();
+
+        Ok(())
+    }
+
+    /// Enable burn-in protection with `config`, or disable it with `None`, at time `now` in
+    /// milliseconds. See the [`burnin`](crate::burnin) module.
+    ///
+    /// Changing the protection moves the image back to its place and shows the display at the
+    /// contrast set with `set_contrast`, waking it up with `delay` if the screensaver put it to
+    /// sleep.
+    pub async fn set_burn_in<DELAY>(
+        &mut self,
+        config: Option<BurnInConfig>,
+        now: u64,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        self.set_idle_state(IdleState::Active, delay).await?;
+        self.burn_in = config.map(|config| BurnIn::new(config, now));
+
+        self.set_pixel_shift((0, 0)).await
+    }
+
+    /// Record activity, e.g. user input, at time `now` in milliseconds. This brightens a dimmed
+    /// display and stops the screensaver of the burn-in protection, waking the display up with
+    /// `delay` if it's asleep.
+    ///
+    /// Redrawing the display doesn't count as activity, as the image may not change much.
+    pub async fn activity<DELAY>(&mut self, now: u64, delay: &mut DELAY) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let Some(burn_in) = &mut self.burn_in else {
+            return Ok(());
+        };
+        burn_in.activity(now);
+
+        self.set_idle_state(IdleState::Active, delay).await
+    }
+
+    /// Run the burn-in protection at time `now` in milliseconds, from the same clock as passed
+    /// to `set_burn_in` and `activity`. Returns whether the display is active, dimmed or showing
+    /// the screensaver, always [`IdleState::Active`] without burn-in protection.
+    ///
+    /// Call it regularly, at least as often as the image should be shifted. A horizontal shift
+    /// sends anything drawn since the last `flush()`, and the columns of display RAM that change
+    /// with the shift. `delay` waits for the charge pump if the display wakes up from the
+    /// screensaver.
+    pub async fn tick<DELAY>(
+        &mut self,
+        now: u64,
+        delay: &mut DELAY,
+    ) -> Result<IdleState, DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let Some(burn_in) = &mut self.burn_in else {
+            return Ok(IdleState::Active);
+        };
+        let state = burn_in.idle_state(now);
+        // the shift is kept while the display sleeps
+        let shift = if burn_in.config.sleeps(state) {
+            None
+        } else {
+            burn_in.shift(now)
+        };
+
+        self.set_idle_state(state, delay).await?;
+        if let Some(shift) = shift {
+            self.set_pixel_shift(shift).await?;
+        }
+
+        Ok(state)
+    }
+
+    /// Mark columns `first_col..=last_col` of pages `first_page..=last_page` to be sent on the
+    /// next flush
+    fn mark_dirty(&mut self, first_page: u32, last_page: u32, first_col: u8, last_col: u8) {
+        for span in self
+            .dirty
+            .iter_mut()
+            .take(last_page as usize + 1)
+            .skip(first_page as usize)
+        {
+            *span = (span.0.min(first_col), span.1.max(last_col));
+        }
+    }
+
+    /// Split buffer `rows` where they wrap around the bottom of the ring buffer set up by
+    /// `set_scroll_offset`, returning the row in display RAM each part starts at
+    fn ring_segments(&self, rows: Range<u32>) -> [(u32, Range<u32>); 2] {
+        let height = self.properties.get_size().1 as u32;
+        let start = (rows.start + self.start_line as u32) % height;
+        let split = rows.start + (rows.len() as u32).min(height - start);
+
+        [(start, rows.start..split), (0, split..rows.end)]
+    }
+
+    /// Write `columns` x `rows` of the buffer a byte at a time, ignoring rotation.
+    /// `fetch(column, row)` returns the pixels of `column` at `row..row + 8`, the top one in bit 0.
+    fn copy_bytes(
+        &mut self,
+        columns: Range<u32>,
+        rows: Range<u32>,
+        fetch: impl Fn(u32, i32) -> u8,
+    ) {
+        let width = self.properties.get_size().0 as usize;
+
+        for (start, rows) in self.ring_segments(rows) {
+            if rows.is_empty() {
+                continue;
+            }
+            let end = start + rows.len() as u32 - 1;
+            self.mark_dirty(start / 8, end / 8, columns.start as u8, (columns.end - 1) as u8);
+
+            for page in start / 8..=end / 8 {
+                let top = page * 8;
+                let (first, last) = (start.max(top) - top, end.min(top + 7) - top);
+                let mask = ((2u16 << last) - (1u16 << first)) as u8;
+                // buffer row drawn to the top row of this page
+                let row = rows.start as i32 + top as i32 - start as i32;
+
+                let page_bytes = &mut self.buffer.as_mut()[page as usize * width..][..width];
+                for column in columns.clone() {
+                    let byte = &mut page_bytes[column as usize];
+                    *byte = (*byte & !mask) | (fetch(column, row) & mask);
+                }
+            }
+        }
+    }
+
+    /// Switch the burn-in protection to `state`, dimming the display and starting or stopping the
+    /// screensaver as configured
+    async fn set_idle_state<DELAY>(
+        &mut self,
+        state: IdleState,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let Some(burn_in) = &self.burn_in else {
+            return Ok(());
+        };
+        let (from, config) = (burn_in.state, burn_in.config);
+        if from == state {
+            return Ok(());
+        }
+
+        if config.sleeps(from) && !config.sleeps(state) {
+            self.set_power_state(PowerState::On, delay).await?;
+        }
+        if let Some(dim) = config.dim_contrast() {
+            if (from == IdleState::Active) != (state == IdleState::Active) {
+                let contrast = if state == IdleState::Active {
+                    self.contrast
+                } else {
+                    dim
+                };
+                self.properties.set_contrast(contrast).await?;
+            }
+        }
+        if config.sleeps(state) && !config.sleeps(from) {
+            self.set_power_state(PowerState::Sleep, delay).await?;
+        }
+
+        if let Some(burn_in) = &mut self.burn_in {
+            burn_in.state = state;
+        }
+
+        Ok(())
+    }
+
+    /// Move the image by `shift` columns and rows, vertically with the display start line and
+    /// horizontally by sending the columns that change
+    async fn set_pixel_shift(&mut self, shift: (u8, u8)) -> Result<(), DisplayError> {
+        let (columns, rows) = shift;
+        if rows != self.shift.1 {
+            // rows of display RAM moving into view from below the buffer may hold anything
+            let blank =
+                self.shift.1 == 0 && self.properties.get_power_state() != PowerState::PowerDown;
+            self.shift.1 = rows;
+            if blank {
+                self.blank_rows_below().await?;
+            }
+            self.properties
+                .set_start_line(self.hardware_start_line(self.start_line))
+                .await?;
+        }
+        if columns != self.shift.0 {
+            // display RAM is in sync with the buffer afterwards, unless it's powered down
+            self.flush().await?;
+            let from = self.shift.0;
+            self.shift.0 = columns;
+            if self.properties.get_power_state() != PowerState::PowerDown {
+                self.send_shifted_columns(from).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send the columns of display RAM that change when the image moves from `from` columns to
+    /// the current horizontal shift. Columns the image moves away from are blanked.
+    async fn send_shifted_columns(&mut self, from: u8) -> Result<(), DisplayError> {
+        let (width, height) = self.properties.get_size();
+        let width = width as usize;
+        let (from, to) = (from as usize, self.shift.0 as usize);
+
+        for page in 0..height.div_ceil(8) as usize {
+            let buf = &self.buffer.as_ref()[page * width..][..width];
+            // byte shown in display column `column` with the image moved by `shift` columns
+            let shown = |shift: usize, column: usize| {
+                column.checked_sub(shift).map_or(0, |column| buf[column])
+            };
+
+            let mut column = 0;
+            while column < width {
+                if shown(from, column) == shown(to, column) {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < width && shown(from, column) != shown(to, column) {
+                    column += 1;
+                }
+
+                let image = start.max(to);
+                Self::blank_columns(&mut self.properties, page as u8, start..image).await?;
+                if image < column {
+                    self.properties
+                        .draw_page(page as u8, image as u8, &buf[image - to..column - to])
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blank display RAM `columns` of `page`
+    async fn blank_columns(
+        properties: &mut DisplayProperties<DV, DI>,
+        page: u8,
+        columns: Range<usize>,
+    ) -> Result<(), DisplayError> {
+        const BLANK: [u8; 16] = [0; 16];
+
+        let mut column = columns.start;
+        while column < columns.end {
+            let len = (columns.end - column).min(BLANK.len());
+            properties
+                .draw_page(page, column as u8, &BLANK[..len])
+                .await?;
+            column += len;
+        }
+
+        Ok(())
+    }
+
+    /// Get the display start line for scroll offset `line`, moved by the vertical pixel shift
+    fn hardware_start_line(&self, line: u8) -> u8 {
+        ((line as u16 + self.shift.1 as u16) % self.ram_rows()) as u8
+    }
+
+    /// Number of rows in display RAM the start line wraps around, controllers for displays of
+    /// up to 64 rows have 64 rows of display RAM
+    fn ram_rows(&self) -> u16 {
+        self.properties.get_size().1.max(64) as u16
+    }
+
+    /// Blank the pages of display RAM below the buffer, which the vertical pixel shift moves into
+    /// view on displays with fewer rows than display RAM
+    async fn blank_rows_below(&mut self) -> Result<(), DisplayError> {
+        let (width, height) = self.properties.get_size();
+
+        for page in height.div_ceil(8)..(self.ram_rows() / 8) as u8 {
+            Self::blank_columns(&mut self.properties, page, 0..width as usize).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the contrast the display is shown at, lowered while dimmed by the burn-in protection
+    fn shown_contrast(&self) -> u8 {
+        match &self.burn_in {
+            Some(burn_in) if burn_in.state != IdleState::Active => {
+                burn_in.config.dim_contrast().unwrap_or(self.contrast)
+            }
+            _ => self.contrast,
+        }
+    }
+
+    /// Forget what display RAM holds, so that the whole buffer is sent without comparing it to
+    /// the shadow on the next flush
+    fn invalidate_shadow(&mut self) {
+        self.mark_all_dirty();
+        self.shadow_stale = u32::MAX;
+    }
+
+    /// Mark the whole buffer to be sent on the next flush
+    fn mark_all_dirty(&mut self) {
+        let width = self.properties.get_size().0;
+        self.dirty = [(0, width - 1); MAX_PAGES];
     }
 
     #[cfg(feature = "graphics")]
     /// Needs y to be a multiple of 8, excess height is ignored
     fn fill_solid_aligned(&mut self, x: u32, y: u32, width: u32, height: u32, fill: u8) {
+        let display_width = self.properties.get_size().0 as u32;
         // fill whole 8px tall chunks
         for block in (y / 8)..((height + y) / 8) {
-            self.buffer[(x + block * DV::WIDTH as u32) as usize..][..width as usize].fill(fill);
+            self.buffer.as_mut()[(x + block * display_width) as usize..][..width as usize]
+                .fill(fill);
+        }
+    }
+    #[cfg(feature = "graphics")]
+    /// Fill a rectangle in display RAM coordinates, which must not wrap around the bottom
+    fn fill_solid_rows(&mut self, x: u32, mut y: u32, width: u32, mut height: u32, color: bool) {
+        self.mark_dirty(y / 8, (y + height - 1) / 8, x as u8, (x + width - 1) as u8);
+
+        // unaligned top
+        let top_offset = y % 8;
+        if top_offset != 0 {
+            let mask_height = core::cmp::min(height, 8 - top_offset);
+            let mask = ((1u8 << mask_height).wrapping_sub(1)) << top_offset;
+            self.apply_mask_to_page(mask, color, (y / 8) as u8, x as u8, width as u8);
+
+            height -= mask_height;
+            y += mask_height;
+        }
+        // potentially many full pages
+        if height != 0 {
+            let fill = if color { 0xff } else { 0 };
+            self.fill_solid_aligned(x, y, width, height, fill);
+        }
+        let bottom_rows = height % 8;
+        if bottom_rows != 0 {
+            let mask = (1u8 << bottom_rows) - 1;
+            let page = (y as u8 + height as u8) / 8;
+            self.apply_mask_to_page(mask, color, page, x as u8, width as u8);
         }
     }
     #[cfg(feature = "graphics")]
     fn apply_mask_to_page(&mut self, mask: u8, color: bool, page: u8, x: u8, width: u8) {
-        let col_offset = x as usize + page as usize * DV::WIDTH as usize;
-        let iter = self.buffer[col_offset..(col_offset + width as usize)].iter_mut();
+        let col_offset = x as usize + page as usize * self.properties.get_size().0 as usize;
+        let iter = self.buffer.as_mut()[col_offset..(col_offset + width as usize)].iter_mut();
         if color {
             iter.for_each(|b| *b |= mask);
         } else {
@@ -246,6 +1037,34 @@ where
     }
 }
 
+/// Find runs of columns in `first..=last` where `buf` differs from `shadow`, merging runs that are
+/// only a few columns apart
+fn changed_runs<'a>(
+    buf: &'a [u8],
+    shadow: &'a [u8],
+    first: usize,
+    last: usize,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let changed = move |col: usize| shadow.get(col) != Some(&buf[col]);
+    let mut col = first;
+
+    core::iter::from_fn(move || {
+        let start = (col..=last).find(|&c| changed(c))?;
+        let mut end = start;
+        for c in start + 1..=last {
+            if c - end > MAX_GAP {
+                break;
+            }
+            if changed(c) {
+                end = c;
+            }
+        }
+        col = end + 1;
+
+        Some((start, end))
+    })
+}
+
 #[cfg(feature = "graphics")]
 use embedded_graphics_core::{
     draw_target::DrawTarget,
@@ -265,10 +1084,11 @@ use embedded_graphics_core::{
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV, DI, const BS: usize> DrawTarget for GraphicsMode<DV, DI, BS>
+impl<DV, DI, B, const SB: usize> DrawTarget for GraphicsMode<DV, DI, B, SB>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
 {
     type Color = BinaryColor;
     type Error = DisplayError;
@@ -289,6 +1109,66 @@ where
         Ok(())
     }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = area.intersection(&self.bounding_box());
+        if drawable.is_zero_sized() {
+            return Ok(());
+        }
+        let Rectangle {
+            top_left: Point { x, y },
+            size: Size { width, height },
+        } = drawable;
+        let columns = x as u32..x as u32 + width;
+        let rows = y as u32..y as u32 + height;
+
+        // colors of the pixels outside of the display are skipped
+        let skip_top = (y - area.top_left.y) as usize * area.size.width as usize;
+        let skip_left = (x - area.top_left.x) as usize;
+        let skip_row = (area.size.width - width) as usize;
+        let mut colors = colors.into_iter().skip(skip_top + skip_left);
+
+        let swap = matches!(
+            self.properties.get_rotation(),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270
+        );
+        let (display_width, display_height) = self.properties.get_size();
+        let (display_width, display_height) = (display_width as u32, display_height as u32);
+        let start_line = self.start_line as u32;
+        let buffer = self.buffer.as_mut();
+
+        for row in rows.clone() {
+            for (column, color) in columns.clone().zip(colors.by_ref()) {
+                let (column, row) = if swap { (row, column) } else { (column, row) };
+                // map into the ring buffer set up by `set_scroll_offset`
+                let row = (row + start_line) % display_height;
+
+                let byte = &mut buffer[(row / 8 * display_width + column) as usize];
+                let bit = 1 << (row % 8);
+                if color.is_on() {
+                    *byte |= bit;
+                } else {
+                    *byte &= !bit;
+                }
+            }
+            if skip_row != 0 {
+                colors.nth(skip_row - 1);
+            }
+        }
+
+        let (columns, rows) = if swap { (rows, columns) } else { (columns, rows) };
+        for (start, rows) in self.ring_segments(rows) {
+            if !rows.is_empty() {
+                let end = start + rows.len() as u32 - 1;
+                self.mark_dirty(start / 8, end / 8, columns.start as u8, (columns.end - 1) as u8);
+            }
+        }
+
+        Ok(())
+    }
+
     fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
         let intersect = area.intersection(&self.bounding_box());
         if intersect.is_zero_sized() { return Ok(()) };
@@ -297,34 +1177,20 @@ where
             size: Size { width, height },
         } = intersect;
         // swap coordinates if rotated
-        let (x, mut y, width, mut height) = match self.properties.get_rotation() {
+        let (x, y, width, height) = match self.properties.get_rotation() {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y, width, height),
             DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x, height, width),
         };
-        self.top_left.0 = self.top_left.0.min(x as u8);
-        self.top_left.1 = self.top_left.1.min(y as u8);
-
-        self.bot_right.0 = self.bot_right.0.max(x as u8 + width as u8 - 1);
-        self.bot_right.1 = self.bot_right.1.max(y as u8 + height as u8 - 1);
 
-        // unaligned top
-        if y % 8 != 0 {
-            let mask_height = core::cmp::min(height, 8 - (y as u32 % 8));
-            let mask = ((1u8 << mask_height).wrapping_sub(1)) << (y % 8);
-            self.apply_mask_to_page(mask, color.is_on(), (y / 8) as u8, x as u8, width as u8);
-
-            height -= mask_height;
-            y += mask_height as i32;
-        }
-        // potentially many full pages
-        if height != 0 {
-            let fill = if color.is_on() { 0xff } else { 0 };
-            self.fill_solid_aligned(x as u32, y as u32, width, height, fill);
-        }
-        if (height % 8) != 0 {
-            let mask = (1u8 << (height % 8)) - 1;
-            let page = (y as u8 + height as u8) / 8;
-            self.apply_mask_to_page(mask, color.is_on(), page, x as u8, width as u8);
+        // map into the ring buffer set up by `set_scroll_offset`, wrapping around at the bottom
+        let display_height = self.properties.get_size().1 as u32;
+        let y = (y as u32 + self.start_line as u32) % display_height;
+        if y + height > display_height {
+            let wrapped = y + height - display_height;
+            self.fill_solid_rows(x as u32, y, width, height - wrapped, color.is_on());
+            self.fill_solid_rows(x as u32, 0, width, wrapped, color.is_on());
+        } else {
+            self.fill_solid_rows(x as u32, y, width, height, color.is_on());
         }
 
         Ok(())
@@ -340,10 +1206,11 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV, DI, const BS: usize> OriginDimensions for GraphicsMode<DV, DI, BS>
+impl<DV, DI, B, const SB: usize> OriginDimensions for GraphicsMode<DV, DI, B, SB>
 where
     DI: AsyncWriteOnlyDataCommand,
     DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
 {
     fn size(&self) -> Size {
         let (w, h) = self.get_dimensions();
@@ -351,3 +1218,323 @@ where
         Size::new(w.into(), h.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use super::changed_runs;
+    use crate::{
+        display::DisplayVariant,
+        displays::{generic::RuntimeVariant, sh1108::Sh1108_64_160, ssd1306::Ssd1306_72_40},
+    };
+
+    #[test]
+    fn default_buffer_fits_variant() {
+        assert_eq!(size_of::<<Ssd1306_72_40 as DisplayVariant>::Buffer>(), 72 * 40 / 8);
+        assert_eq!(size_of::<<Sh1108_64_160 as DisplayVariant>::Buffer>(), 64 * 160 / 8);
+        assert_eq!(size_of::<<RuntimeVariant as DisplayVariant>::Buffer>(), 160 * 160 / 8);
+    }
+
+    #[test]
+    fn changed_runs_merge_small_gaps() {
+        let shadow = [0u8; 16];
+        let mut buf = [0u8; 16];
+        for col in [1, 2, 5, 12] {
+            buf[col] = 0xFF;
+        }
+
+        let mut runs = changed_runs(&buf, &shadow, 0, 15);
+        assert_eq!(runs.next(), Some((1, 5)));
+        assert_eq!(runs.next(), Some((12, 12)));
+        assert_eq!(runs.next(), None);
+        assert_eq!(changed_runs(&buf, &shadow, 6, 11).next(), None);
+        assert_eq!(changed_runs(&buf, &[], 14, 15).count(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn flush_sends_only_dirty_spans() {
+        use core::convert::identity;
+        use std::vec::Vec;
+
+        use display_interface::DataFormat;
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64, prelude::*, sim::block_on, test_helpers::setup,
+        };
+
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        // fill display RAM, so that any column sent by the flush shows up as cleared
+        for page in 0..8 {
+            let mut emulator = emulator.borrow_mut();
+            emulator
+                .send_commands_sync(DataFormat::U8(&[0xB0 | page, 0x00, 0x10]))
+                .unwrap();
+            emulator.send_data_sync(DataFormat::U8(&[0xFF; 128])).unwrap();
+        }
+
+        display.set_pixel(0, 0, 0);
+        display.set_pixel(1, 0, 0);
+        display.set_pixel(127, 63, 0);
+        display.set_pixel(126, 60, 0);
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        let cleared: Vec<_> = (0..64)
+            .flat_map(|y| (0..128).map(move |x| (x, y)))
+            .filter(|&(x, y)| !emulator.ram_pixel(x, y))
+            .map(|(x, y)| (x, y / 8))
+            .collect();
+        assert!(cleared.iter().all(|&(x, page)| matches!((x, page), (0 | 1, 0) | (126 | 127, 7))));
+        assert_eq!(cleared.len(), 4 * 8);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shadow_buffer_skips_unchanged_columns() {
+        use core::convert::identity;
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64, prelude::*, sim::block_on, test_helpers::setup,
+        };
+
+        let (mut display, emulator): (GraphicsMode<_, _, [u8; 1024], 1024>, _) =
+            setup(Ssd1306_128_64 {}, identity);
+
+        display.set_pixel(100, 60, 1);
+        block_on(display.flush()).unwrap();
+
+        // redrawing the same pixel on page 7 is skipped, so the last write is to page 0
+        display.set_pixel(100, 60, 1);
+        display.set_pixel(0, 0, 1);
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        assert_eq!(emulator.address(), (0, 1));
+        assert!(emulator.pixel(0, 0));
+        assert!(emulator.pixel(100, 60));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn external_buffer_is_size_checked() {
+        use core::convert::identity;
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64, mode::RawMode, prelude::*, sim::block_on,
+            test_helpers::setup,
+        };
+
+        let mut small = [0u8; 512];
+        let (raw, _): (RawMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        assert!(GraphicsMode::<_, _, &mut [u8]>::with_buffer(raw, &mut small[..]).is_err());
+
+        let mut buffer = [0u8; 1024];
+        let (raw, _): (RawMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let shadowed = GraphicsMode::<_, _, &mut [u8], 512>::with_buffer(raw, &mut buffer[..]);
+        assert!(shadowed.is_err());
+
+        let mut buffer = [0u8; 1024];
+        let (raw, emulator): (RawMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let mut display: GraphicsMode<_, _, &mut [u8]> =
+            GraphicsMode::with_buffer(raw, &mut buffer[..]).unwrap();
+
+        display.set_pixel(3, 4, 1);
+        block_on(display.flush()).unwrap();
+
+        assert!(emulator.borrow().pixel(3, 4));
+        assert_eq!(buffer[3], 1 << 4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn flush_step_resumes_where_it_stopped() {
+        use core::convert::identity;
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64, prelude::*, sim::block_on, test_helpers::setup,
+        };
+
+        let (mut display, emulator): (GraphicsMode<_, _, [u8; 1024], 1024>, _) =
+            setup(Ssd1306_128_64 {}, identity);
+
+        block_on(display.flush()).unwrap();
+        for x in 0..100 {
+            display.set_pixel(x, 0, 1);
+        }
+        display.set_pixel(127, 63, 1);
+        assert!(!block_on(display.flush_step(64)).unwrap());
+
+        // drawn over a column that was already sent, only it and the 36 unsent columns of page 0
+        // differ from the shadow, which leaves room for page 7 in the next step
+        display.set_pixel(10, 1, 1);
+        assert!(block_on(display.flush_step(64)).unwrap());
+        assert!(block_on(display.flush_step(64)).unwrap());
+
+        let frame = display.frame();
+        assert_eq!(frame.diff(&emulator.borrow().frame(DisplayRotation::Rotate0)), []);
+    }
+
+    /// 13x11 bitmap that looks different after every rotation and flip
+    #[cfg(feature = "std")]
+    fn sprite(x: u32, y: u32) -> bool {
+        x == 0 || y == 10 || (x + y) % 5 == 2 || (x == 9 && y < 4)
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn blit_matches_set_pixel() {
+        use std::vec::Vec;
+
+        use crate::{
+            bitmap::{Bitmap, BitmapLayout},
+            displays::ssd1306::Ssd1306_128_64,
+            prelude::*,
+            sim::{block_on, Emulator},
+            Builder,
+        };
+
+        let (width, height) = (13, 11);
+        let mut row_major = Vec::new();
+        for y in 0..height {
+            for byte in 0..2 {
+                row_major.push((0..8).fold(0, |bits, i| {
+                    bits | ((sprite(byte * 8 + i, y) && byte * 8 + i < width) as u8) << (7 - i)
+                }));
+            }
+        }
+        let mut page_major = Vec::new();
+        for page in 0..2 {
+            for x in 0..width {
+                page_major.push((0..8).fold(0, |bits, i| {
+                    bits | ((sprite(x, page * 8 + i) && page * 8 + i < height) as u8) << i
+                }));
+            }
+        }
+        let bitmaps = [
+            Bitmap::new(&row_major, width, height, BitmapLayout::RowMajor),
+            Bitmap::new(&page_major, width, height, BitmapLayout::PageMajor),
+        ];
+
+        for rotation in [DisplayRotation::Rotate0, DisplayRotation::Rotate90] {
+            for bitmap in &bitmaps {
+                for (x, y, scroll) in [(-3, -5, 0), (5, 3, 0), (59, 121, 0), (20, 12, 13)] {
+                    let variant = Ssd1306_128_64 {};
+                    let mut blitted = Emulator::new(&variant);
+                    let mut expected = Emulator::new(&variant);
+                    let raw = Builder::new(variant).with_rotation(rotation).connect(&mut blitted);
+                    let mut blit: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+                    let raw = Builder::new(variant).with_rotation(rotation).connect(&mut expected);
+                    let mut pixels: GraphicsMode<_, _> =
+                        block_on(raw.init_without_reset()).unwrap();
+
+                    for display in [&mut blit, &mut pixels] {
+                        block_on(display.set_scroll_offset(scroll)).unwrap();
+                        // background to overwrite
+                        for i in 0..64 {
+                            display.set_pixel(i, i, 1);
+                            display.set_pixel(i + 1, i, 1);
+                        }
+                    }
+                    blit.blit(x, y, bitmap);
+                    for by in 0..height {
+                        for bx in 0..width {
+                            let value = sprite(bx, by) as u8;
+                            pixels.set_pixel((x + bx as i32) as u32, (y + by as i32) as u32, value);
+                        }
+                    }
+                    block_on(blit.flush()).unwrap();
+                    block_on(pixels.flush()).unwrap();
+
+                    assert_eq!(blit.frame().diff(&pixels.frame()), []);
+                    assert_eq!(blitted.frame(rotation).diff(&expected.frame(rotation)), []);
+                }
+            }
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "graphics"))]
+    #[test]
+    fn fill_contiguous_clips_to_display() {
+        use embedded_graphics::{
+            image::{Image, ImageRaw},
+            pixelcolor::BinaryColor,
+            prelude::*,
+        };
+
+        use crate::{
+            displays::ssd1306::Ssd1306_128_64,
+            prelude::*,
+            sim::{block_on, Emulator},
+            Builder,
+        };
+
+        // 16x3 image, a different pattern in each row
+        let data = [0xF0, 0x0F, 0xAA, 0x55, 0x81, 0x18];
+        let image = ImageRaw::<BinaryColor>::new(&data, 16);
+
+        for rotation in [DisplayRotation::Rotate0, DisplayRotation::Rotate270] {
+            let variant = Ssd1306_128_64 {};
+            let mut emulator = Emulator::new(&variant);
+            let raw = Builder::new(variant).with_rotation(rotation).connect(&mut emulator);
+            let mut display: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+
+            block_on(display.set_scroll_offset(7)).unwrap();
+            Image::new(&image, Point::new(-4, -1)).draw(&mut display).unwrap();
+            block_on(display.flush()).unwrap();
+
+            let frame = display.frame();
+            for y in 0..2 {
+                for x in 0..12 {
+                    let bit = data[(y + 1) * 2 + (x + 4) / 8] & (0x80 >> ((x + 4) % 8)) != 0;
+                    assert_eq!(frame.pixel(x as u32, y as u32), bit);
+                }
+            }
+            assert_eq!(frame.diff(&emulator.frame(rotation)), []);
+            assert!(!frame.pixel(12, 0) && !frame.pixel(0, 2));
+        }
+    }
+
+    #[cfg(all(feature = "std", feature = "graphics"))]
+    #[test]
+    fn page_image_draws_like_blit() {
+        use embedded_graphics::{
+            image::{Image, ImageDrawable},
+            prelude::*,
+            primitives::Rectangle,
+        };
+
+        use crate::{
+            bitmap::{page_major, Bitmap, BitmapLayout, PageImage},
+            displays::ssd1306::Ssd1306_128_64,
+            prelude::*,
+            sim::{block_on, Emulator},
+            Builder,
+        };
+
+        const RAW: &[u8] = include_bytes!("../../examples/rust.raw");
+        const LOGO_DATA: [u8; 512] = page_major(RAW, 64, 64);
+        const LOGO: PageImage = PageImage::new(&LOGO_DATA, 64, 64);
+
+        let variant = Ssd1306_128_64 {};
+        let (mut first, mut second) = (Emulator::new(&variant), Emulator::new(&variant));
+        let raw = Builder::new(variant).connect(&mut first);
+        let mut drawn: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+        let raw = Builder::new(variant).connect(&mut second);
+        let mut blitted: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+
+        Image::new(&LOGO, Point::new(30, 5)).draw(&mut drawn).unwrap();
+        // the top left of the area is drawn at -10, 50 even though it is outside of the image
+        let area = Rectangle::new(Point::new(40, -8), Size::new(30, 20));
+        let mut target = drawn.translated(Point::new(-10, 50));
+        LOGO.draw_sub_image(&mut target, &area).unwrap();
+
+        let raw = Bitmap::new(RAW, 64, 64, BitmapLayout::RowMajor);
+        blitted.blit(30, 5, &raw);
+        // 40..64, 0..12 of the image is drawn at -10..14, 58..70 and clipped to the display
+        blitted.blit(-50, 58, &LOGO.bitmap());
+
+        assert_eq!(drawn.frame().diff(&blitted.frame()), []);
+    }
+}
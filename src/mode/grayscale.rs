@@ -0,0 +1,646 @@
+//! Buffered 4 bit grayscale display module for use with the [embedded-graphics] crate
+//!
+//! ```rust,no_run
+//!
+//! use embedded_graphics::{
+//!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
+//!     pixelcolor::Gray4,
+//!     prelude::*,
+//!     text::{Baseline, Text},
+//! };
+//! async fn run_display(display_interface: SomeInstanceOfDisplayInterface) {
+//!     let mut disp: GrayscaleMode<_, _> = Builder::new(Ssd1327_128_128 {})
+//!         .connect(display_interface)
+//!         .init(&mut reset, &mut delay)
+//!         .await
+//!         .unwrap();
+//!
+//!     disp.clear();
+//!     disp.flush().await.unwrap();
+//!
+//!     let text_style = MonoTextStyleBuilder::new()
+//!         .font(&FONT_6X10)
+//!         .text_color(Gray4::new(0x8))
+//!         .build();
+//!     Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
+//!         .draw(&mut disp)
+//!         .unwrap();
+//!
+//!     disp.flush().await.unwrap();
+//! }
+//! ```
+//!
+//! Rotation and mirroring are done in software, as the grayscale controllers don't share the
+//! remap commands of the monochrome ones.
+
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    display,
+    displayrotation::{DisplayRotation, Mirror},
+    mode::displaymode::{sealed::NewMode, DisplayModeTrait},
+    power::{PowerControl, PowerState},
+    properties::DisplayProperties,
+};
+
+const DEFAULT_BUFFER_SIZE: usize = 256 * 64 / 2;
+
+/// Grayscale mode handler
+///
+/// `BS` is the size of the buffer in bytes, which must be at least
+/// [`GrayscaleDisplayVariant::BUFFER_SIZE`](display::GrayscaleDisplayVariant::BUFFER_SIZE). The
+/// default fits all displays included in this crate, a smaller one fails to compile.
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub struct GrayscaleMode<DV, DI, const BS: usize = DEFAULT_BUFFER_SIZE>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    properties: DisplayProperties<DV, DI>,
+    buffer: [u8; BS],
+    rotation: DisplayRotation,
+    mirror: Mirror,
+    top_left: (u16, u16),
+    bot_right: (u16, u16),
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> NewMode<DV, DI> for GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    /// Create new GrayscaleMode instance
+    fn new(properties: DisplayProperties<DV, DI>) -> Self {
+        let () = Self::BUFFER_SIZE_CHECK;
+        let rotation = properties.get_rotation();
+        let mirror = properties.get_mirror();
+        GrayscaleMode {
+            properties,
+            buffer: [0u8; BS],
+            rotation,
+            mirror,
+            top_left: (DV::WIDTH, DV::HEIGHT),
+            bot_right: (0, 0),
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> DisplayModeTrait<DV, DI> for GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    /// Release all resources used by GrayscaleMode
+    fn release(self) -> DisplayProperties<DV, DI> {
+        self.properties
+    }
+
+    async fn init(&mut self) -> Result<(), DisplayError> {
+        GrayscaleMode::init(self).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> PowerControl for GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        GrayscaleMode::display_on(self, on).await
+    }
+
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let state = if sleep { PowerState::Sleep } else { PowerState::On };
+        self.properties
+            .set_grayscale_power_state(state, delay)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    /// Fails to compile when a display mode is created with a buffer too small for the display
+    const BUFFER_SIZE_CHECK: () = assert!(
+        BS >= DV::BUFFER_SIZE,
+        "buffer is smaller than GrayscaleDisplayVariant::BUFFER_SIZE"
+    );
+
+    /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.buffer = [0; BS];
+        self.top_left = (0, 0);
+        self.bot_right = (DV::WIDTH - 1, DV::HEIGHT - 1);
+    }
+
+    /// Write out data to display
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        // nothing drawn since last flush
+        if self.top_left.0 > self.bot_right.0 || self.top_left.1 > self.bot_right.1 {
+            return Ok(());
+        }
+
+        // two pixels per byte, so widen the modified area to whole bytes
+        let base_col = self.top_left.0 & !1;
+        let end_col = self.bot_right.0 | 1;
+        let width = end_col - base_col + 1;
+
+        let row_bytes = DV::WIDTH as usize / 2;
+        let first = self.top_left.1 as usize * row_bytes;
+        let last = (self.bot_right.1 as usize + 1) * row_bytes;
+
+        if width == DV::WIDTH {
+            // whole rows are contiguous in the buffer
+            self.properties
+                .draw_window(0, self.top_left.1, width, &self.buffer[first..last])
+                .await?;
+        } else {
+            for (row, buf) in self.buffer[first..last].chunks_exact(row_bytes).enumerate() {
+                // crop to columns in the modified area
+                let buf = &buf[(base_col as usize / 2)..=(end_col as usize / 2)];
+                self.properties
+                    .draw_window(base_col, self.top_left.1 + row as u16, width, buf)
+                    .await?;
+            }
+        }
+
+        self.top_left = (DV::WIDTH, DV::HEIGHT);
+        self.bot_right = (0, 0);
+
+        Ok(())
+    }
+
+    /// Set a pixel to a gray level from `0` (off) to `15` (full brightness), higher bits of
+    /// `value` are ignored. If the X and Y coordinates are out of the bounds of the display, this
+    /// method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (width, height) = (DV::WIDTH as u32, DV::HEIGHT as u32);
+
+        // mirroring works on the axes of the rotated image
+        let (dim_x, dim_y) = self.get_dimensions();
+        let (flip_x, flip_y) = self.mirror.flips();
+        let x = if flip_x { (dim_x as u32).wrapping_sub(1 + x) } else { x };
+        let y = if flip_y { (dim_y as u32).wrapping_sub(1 + y) } else { y };
+        let (x, y) = match self.rotation {
+            DisplayRotation::Rotate0 => (x, y),
+            DisplayRotation::Rotate90 => (width.wrapping_sub(1 + y), x),
+            DisplayRotation::Rotate180 => (width.wrapping_sub(1 + x), height.wrapping_sub(1 + y)),
+            DisplayRotation::Rotate270 => (y, height.wrapping_sub(1 + x)),
+        };
+
+        if x >= width || y >= height {
+            return;
+        }
+        let (x, y) = (x as u16, y as u16);
+
+        self.top_left.0 = self.top_left.0.min(x);
+        self.top_left.1 = self.top_left.1.min(y);
+
+        self.bot_right.0 = self.bot_right.0.max(x);
+        self.bot_right.1 = self.bot_right.1.max(y);
+
+        let idx = y as usize * (DV::WIDTH as usize / 2) + x as usize / 2;
+
+        // left pixel of the pair lives in the high nibble
+        let value = value & 0xF;
+        if x % 2 == 0 {
+            self.buffer[idx] = (self.buffer[idx] & 0x0F) | (value << 4);
+        } else {
+            self.buffer[idx] = (self.buffer[idx] & 0xF0) | value;
+        }
+    }
+
+    /// Initialise the display
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        self.properties.init_grayscale().await
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        let (w, h) = DV::dimensions();
+
+        match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
+        }
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+
+    /// Set the display rotation. Only affects pixels drawn after this call, the buffer is not
+    /// rotated.
+    pub fn set_rotation(&mut self, rot: DisplayRotation) {
+        self.rotation = rot;
+    }
+
+    /// Get the display mirroring
+    pub fn get_mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Set the display mirroring, applied on top of the rotation. Only affects pixels drawn after
+    /// this call, the buffer is not mirrored.
+    pub fn set_mirror(&mut self, mirror: Mirror) {
+        self.mirror = mirror;
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        self.properties.display_on(on).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+        self.properties.set_contrast(contrast).await
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::{Gray4, GrayColor},
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> DrawTarget for GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    type Color = Gray4;
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u32, pos.y as u32, color.luma()));
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const BS: usize> OriginDimensions for GrayscaleMode<DV, DI, BS>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::GrayscaleDisplayVariant,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    #[cfg(not(feature = "blocking"))]
+    use display_interface::AsyncWriteOnlyDataCommand;
+    use display_interface::{DataFormat, DisplayError};
+    #[cfg(feature = "blocking")]
+    use display_interface::WriteOnlyDataCommand;
+
+    use std::{cell::RefCell, vec, vec::Vec};
+
+    use super::GrayscaleMode;
+    use crate::{
+        display::{InitConfig, VcomhLevel},
+        displayrotation::{DisplayRotation, Mirror},
+        displays::{sh1122::Sh1122_256_64, ssd1327::Ssd1327_128_128},
+        power::PowerControl,
+        sim::block_on,
+        Builder,
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Sent {
+        Commands(Vec<u8>),
+        Data(Vec<u8>),
+    }
+
+    /// Interface that records every transaction, the emulator only knows monochrome controllers
+    struct Recorder<'a>(&'a RefCell<Vec<Sent>>);
+
+    #[maybe_async_cfg::maybe(
+        sync(
+            feature = "blocking",
+            keep_self,
+            idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+        ),
+        async(not(feature = "blocking"), keep_self)
+    )]
+    impl AsyncWriteOnlyDataCommand for Recorder<'_> {
+        async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            match cmd {
+                DataFormat::U8(bytes) => self.0.borrow_mut().push(Sent::Commands(bytes.to_vec())),
+                _ => return Err(DisplayError::DataFormatNotImplemented),
+            }
+            Ok(())
+        }
+
+        async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            match buf {
+                DataFormat::U8(bytes) => self.0.borrow_mut().push(Sent::Data(bytes.to_vec())),
+                _ => return Err(DisplayError::DataFormatNotImplemented),
+            }
+            Ok(())
+        }
+    }
+
+    /// Get the position and level of every lit pixel in a buffer `width` pixels wide
+    fn lit(buffer: &[u8], width: usize) -> Vec<(usize, usize, u8)> {
+        let mut pixels = Vec::new();
+        for (i, byte) in buffer.iter().enumerate() {
+            let (x, y) = (i % (width / 2) * 2, i / (width / 2));
+            for (x, level) in [(x, byte >> 4), (x + 1, byte & 0xF)] {
+                if level != 0 {
+                    pixels.push((x, y, level));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn pixels_are_nibble_packed() {
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Ssd1327_128_128 {}).connect(Recorder(&log));
+        let mut display: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+
+        display.set_pixel(0, 0, 0xA);
+        display.set_pixel(1, 0, 0x5);
+        display.set_pixel(3, 1, 0x1F);
+        display.set_pixel(128, 0, 0xF);
+        assert_eq!(display.buffer[0], 0xA5);
+        assert_eq!(display.buffer[64 + 1], 0x0F);
+
+        // the other pixel of the pair is kept
+        display.set_pixel(0, 0, 0x3);
+        assert_eq!(display.buffer[0], 0x35);
+        assert_eq!(lit(&display.buffer, 128), [(0, 0, 0x3), (1, 0, 0x5), (3, 1, 0xF)]);
+    }
+
+    #[test]
+    fn init_sends_init_config() {
+        let config = InitConfig {
+            contrast: 0x42,
+            precharge: (0x3, 0x7),
+            vcomh: VcomhLevel::V083,
+            clock_div: (0x9, 0x2),
+            ..InitConfig::default()
+        };
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Ssd1327_128_128 {})
+            .with_init_config(config)
+            .connect(Recorder(&log));
+        let _: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+
+        let log = log.into_inner();
+        let Sent::Commands(init) = &log[0] else {
+            panic!("no init commands sent");
+        };
+        for command in [[0x81, 0x42], [0xB1, 0x73], [0xB3, 0x92], [0xBE, 0x05]] {
+            assert!(init.windows(2).any(|bytes| bytes == command));
+        }
+
+        let config = InitConfig {
+            vcomh: VcomhLevel::V065,
+            ..config
+        };
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Sh1122_256_64 {})
+            .with_init_config(config)
+            .connect(Recorder(&log));
+        let _: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+
+        let log = log.into_inner();
+        let Sent::Commands(init) = &log[0] else {
+            panic!("no init commands sent");
+        };
+        for command in [[0x81, 0x42], [0xD9, 0x73], [0xD5, 0x92], [0xDB, 0x10]] {
+            assert!(init.windows(2).any(|bytes| bytes == command));
+        }
+    }
+
+    #[test]
+    fn ssd1327_flush_sends_one_window_per_row() {
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Ssd1327_128_128 {}).connect(Recorder(&log));
+        let mut display: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+        log.borrow_mut().clear();
+
+        display.set_pixel(10, 3, 0x7);
+        display.set_pixel(13, 4, 0x9);
+        block_on(display.flush()).unwrap();
+
+        // columns 10..=13 are two bytes wide, sent as a window for each row
+        assert_eq!(
+            log.take(),
+            [
+                Sent::Commands(vec![0x15, 5, 6, 0x75, 3, 3]),
+                Sent::Data(vec![0x70, 0x00]),
+                Sent::Commands(vec![0x15, 5, 6, 0x75, 4, 4]),
+                Sent::Data(vec![0x00, 0x09]),
+            ]
+        );
+
+        // nothing drawn since
+        block_on(display.flush()).unwrap();
+        assert!(log.borrow().is_empty());
+
+        // whole rows go out as a single window
+        display.clear();
+        block_on(display.flush()).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Sent::Commands(vec![0x15, 0, 63, 0x75, 0, 127]),
+                Sent::Data(vec![0; 128 * 128 / 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn sh1122_flush_addresses_every_row() {
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Sh1122_256_64 {}).connect(Recorder(&log));
+        let mut display: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+        log.borrow_mut().clear();
+
+        display.set_pixel(34, 2, 0xC);
+        display.set_pixel(35, 3, 0x1);
+        block_on(display.flush()).unwrap();
+
+        // byte column 17 is split into a low and a high nibble command
+        assert_eq!(
+            log.take(),
+            [
+                Sent::Commands(vec![0xB0, 2, 0x01, 0x11]),
+                Sent::Data(vec![0xC0]),
+                Sent::Commands(vec![0xB0, 3, 0x01, 0x11]),
+                Sent::Data(vec![0x01]),
+            ]
+        );
+
+        display.clear();
+        block_on(display.flush()).unwrap();
+        let log = log.take();
+        assert_eq!(log.len(), 2 * 64);
+        assert_eq!(log[126], Sent::Commands(vec![0xB0, 63, 0x00, 0x10]));
+        assert_eq!(log[127], Sent::Data(vec![0; 128]));
+    }
+
+    #[test]
+    fn sleep_switches_dc_dc_converter() {
+        #[cfg(feature = "blocking")]
+        use hal::delay::DelayNs;
+        #[cfg(not(feature = "blocking"))]
+        use embedded_hal_async::delay::DelayNs;
+
+        /// Delay adding up the time waited
+        struct Waited(u64);
+
+        #[maybe_async_cfg::maybe(
+            sync(feature = "blocking", keep_self),
+            async(not(feature = "blocking"), keep_self)
+        )]
+        impl DelayNs for Waited {
+            async fn delay_ns(&mut self, ns: u32) {
+                self.0 += ns as u64;
+            }
+        }
+
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Sh1122_256_64 {}).connect(Recorder(&log));
+        let display: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+        log.borrow_mut().clear();
+
+        let sleeping = block_on(display.sleep()).unwrap();
+        assert_eq!(log.take(), [Sent::Commands(vec![0xAE, 0xAD, 0x8A])]);
+
+        // the panel is turned on once the converter has settled
+        let mut delay = Waited(0);
+        let _display = block_on(sleeping.wake(&mut delay)).unwrap();
+        assert_eq!(
+            log.take(),
+            [Sent::Commands(vec![0xAD, 0x8B]), Sent::Commands(vec![0xAF])]
+        );
+        assert!(delay.0 >= 100_000_000);
+    }
+
+    #[test]
+    fn rotation_and_mirror_map_in_software() {
+        let log = RefCell::new(Vec::new());
+        let uninit = Builder::new(Sh1122_256_64 {}).connect(Recorder(&log));
+        let mut display: GrayscaleMode<_, _> = block_on(uninit.init_without_reset()).unwrap();
+
+        for (rotation, mirror, expected) in [
+            (DisplayRotation::Rotate0, Mirror::None, (1, 2)),
+            (DisplayRotation::Rotate90, Mirror::None, (253, 1)),
+            (DisplayRotation::Rotate180, Mirror::None, (254, 61)),
+            (DisplayRotation::Rotate270, Mirror::None, (2, 62)),
+            (DisplayRotation::Rotate0, Mirror::Horizontal, (254, 2)),
+            (DisplayRotation::Rotate0, Mirror::Vertical, (1, 61)),
+            (DisplayRotation::Rotate90, Mirror::Horizontal, (253, 62)),
+            (DisplayRotation::Rotate90, Mirror::Both, (2, 62)),
+        ] {
+            display.clear();
+            display.set_rotation(rotation);
+            display.set_mirror(mirror);
+            display.set_pixel(1, 2, 0xF);
+            assert_eq!(lit(&display.buffer, 256), [(expected.0, expected.1, 0xF)]);
+        }
+
+        // x runs along the 64 rows of the panel when rotated
+        display.clear();
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(display.get_dimensions(), (64, 256));
+        display.set_pixel(64, 0, 0xF);
+        assert!(lit(&display.buffer, 256).is_empty());
+    }
+}
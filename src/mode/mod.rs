@@ -5,6 +5,12 @@
 
 pub mod displaymode;
 pub mod graphics;
+pub mod grayscale;
+pub mod paged;
 pub mod raw;
+pub mod terminal;
 
-pub use self::{graphics::GraphicsMode, raw::RawMode};
+pub use self::{
+    graphics::GraphicsMode, grayscale::GrayscaleMode, paged::PagedMode, raw::RawMode,
+    terminal::TerminalMode,
+};
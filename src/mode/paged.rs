@@ -0,0 +1,461 @@
+//! Page at a time rendering for RAM constrained microcontrollers
+//!
+//! Instead of a framebuffer for the whole display, [`PagedMode`] keeps a buffer for a single
+//! display page, an 8 pixel tall band across the display. `draw()` calls the render closure once
+//! per page and sends each page as soon as it is rendered. Drawing is clipped to the current page,
+//! so the closure has to draw the same scene every time it is called:
+//!
+//! ```rust,no_run
+//! use embedded_graphics::{
+//!     mono_font::{ascii::FONT_6X10, MonoTextStyle},
+//!     pixelcolor::BinaryColor,
+//!     prelude::*,
+//!     primitives::{Circle, PrimitiveStyle},
+//!     text::Text,
+//! };
+//!
+//! let mut disp: PagedMode<_, _> = Builder::new(Sh1107_128_128 {})
+//!     .connect(display_interface)
+//!     .init(&mut reset, &mut delay)
+//!     .await
+//!     .unwrap();
+//!
+//! disp.draw(|page| {
+//!     let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+//!     Text::new("Hello world!", Point::new(0, 10), style).draw(page).unwrap();
+//!     Circle::new(Point::new(40, 40), 48)
+//!         .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+//!         .draw(page)
+//!         .unwrap();
+//! })
+//! .await
+//! .unwrap();
+//! ```
+//!
+//! This trades CPU time for RAM: a 160x160 display needs 160 bytes instead of 3200, but the scene
+//! is drawn 20 times. Every call to `draw()` sends the whole display.
+
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    display,
+    displayrotation::DisplayRotation,
+    mode::displaymode::{sealed::NewMode, DisplayModeTrait},
+    power::{PowerControl, PowerState},
+    properties::DisplayProperties,
+};
+
+/// Paged mode handler
+///
+/// `B` is the page buffer. By default it's the array sized for the display variant,
+/// [`DisplayVariant::PageBuffer`](display::DisplayVariant::PageBuffer). Another array can be
+/// picked as `PagedMode<_, _, [u8; 160]>`, which fails to compile if it's narrower than
+/// [`DisplayVariant::WIDTH`](display::DisplayVariant::WIDTH).
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub struct PagedMode<DV, DI, B = <DV as display::DisplayVariant>::PageBuffer>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    properties: DisplayProperties<DV, DI>,
+    buffer: B,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const PS: usize> NewMode<DV, DI> for PagedMode<DV, DI, [u8; PS]>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Create new PagedMode instance
+    fn new(properties: DisplayProperties<DV, DI>) -> Self {
+        let () = Self::PAGE_SIZE_CHECK;
+
+        PagedMode {
+            properties,
+            buffer: [0u8; PS],
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const PS: usize> DisplayModeTrait<DV, DI> for PagedMode<DV, DI, [u8; PS]>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Release all resources used by PagedMode
+    fn release(self) -> DisplayProperties<DV, DI> {
+        self.properties
+    }
+
+    async fn init(&mut self) -> Result<(), DisplayError> {
+        PagedMode::init(self).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, B> PowerControl for PagedMode<DV, DI, B>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        PagedMode::display_on(self, on).await
+    }
+
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let state = if sleep { PowerState::Sleep } else { PowerState::On };
+        self.properties.set_power_state(state, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, const PS: usize> PagedMode<DV, DI, [u8; PS]>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Fails to compile when a display mode is created with a page buffer narrower than the
+    /// display
+    const PAGE_SIZE_CHECK: () = assert!(
+        PS >= DV::WIDTH as usize,
+        "page buffer is narrower than DisplayVariant::WIDTH"
+    );
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI, B> PagedMode<DV, DI, B>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
+    /// column 0 on the left, to column _n_ on the right
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        self.properties.init_column_mode().await
+    }
+
+    /// Render the display one page at a time, calling `render` with each page in turn
+    pub async fn draw<F>(&mut self, mut render: F) -> Result<(), DisplayError>
+    where
+        F: FnMut(&mut Page<'_>),
+    {
+        let size = self.properties.get_size();
+        let rotation = self.properties.get_rotation();
+        let buffer = &mut self.buffer.as_mut()[..size.0 as usize];
+
+        for index in 0..size.1.div_ceil(8) {
+            buffer.fill(0);
+            render(&mut Page {
+                buffer: &mut *buffer,
+                index,
+                size,
+                rotation,
+            });
+            self.properties.draw_page(index, 0, buffer).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Set the display rotation. Takes effect on the next call to `draw()`.
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+        self.properties.set_rotation(rot).await
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        self.properties.display_on(on).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+        self.properties.set_contrast(contrast).await
+    }
+}
+
+/// A single page being rendered by [`PagedMode::draw`]
+///
+/// Coordinates cover the whole display, pixels outside of the page are ignored.
+pub struct Page<'a> {
+    buffer: &'a mut [u8],
+    index: u8,
+    /// Display size, ignoring the rotation
+    size: (u8, u8),
+    rotation: DisplayRotation,
+}
+
+impl Page<'_> {
+    /// Get the index of the page in display RAM, counted from the top of the unrotated display
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        let (w, h) = self.size;
+
+        match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
+        }
+    }
+
+    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the pixel is
+    /// outside of this page or the display, this method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (x, y) = match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+        };
+        if y / 8 != self.index as u32 || y >= self.size.1 as u32 {
+            return;
+        }
+
+        if let Some(byte) = self.buffer.get_mut(x as usize) {
+            let bit = 1 << (y % 8);
+            if value == 0 {
+                *byte &= !bit;
+            } else {
+                *byte |= bit;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    prelude::Point,
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl DrawTarget for Page<'_> {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u32, pos.y as u32, color.is_on().into())
+            });
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let intersect = area.intersection(&self.bounding_box());
+        if intersect.is_zero_sized() {
+            return Ok(());
+        }
+        let Rectangle {
+            top_left: Point { x, y },
+            size: Size { width, height },
+        } = intersect;
+        // swap coordinates if rotated
+        let (x, y, width, height) = match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                (x as u32, y as u32, width, height)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                (y as u32, x as u32, height, width)
+            }
+        };
+
+        // clip to the rows of this page
+        let top = self.index as u32 * 8;
+        let first = y.clamp(top, top + 8) - top;
+        let last = (y + height).clamp(top, top + 8) - top;
+        if first == last {
+            return Ok(());
+        }
+        let mask = ((1u16 << last) - (1u16 << first)) as u8;
+
+        let columns = self.buffer.iter_mut().skip(x as usize).take(width as usize);
+        if color.is_on() {
+            columns.for_each(|b| *b |= mask);
+        } else {
+            columns.for_each(|b| *b &= !mask);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl OriginDimensions for Page<'_> {
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::convert::identity;
+
+    use super::PagedMode;
+    use crate::{
+        displays::sh1106::Sh1106_128_64, prelude::*, screenshot::Frame, sim::block_on,
+        test_helpers::setup,
+    };
+
+    /// A diagonal line and a box crossing page boundaries
+    fn pattern(x: u32, y: u32) -> bool {
+        x == y || ((20..50).contains(&x) && (5..13).contains(&y))
+    }
+
+    #[test]
+    fn renders_every_page() {
+        for rotation in [DisplayRotation::Rotate0, DisplayRotation::Rotate90] {
+            let (mut display, emulator): (PagedMode<_, _>, _) =
+                setup(Sh1106_128_64 {}, |builder| builder.with_rotation(rotation));
+
+            let (width, height) = display.get_dimensions();
+            let (width, height) = (width as u32, height as u32);
+            block_on(display.draw(|page| {
+                for y in 0..height {
+                    for x in 0..width {
+                        page.set_pixel(x, y, pattern(x, y) as u8);
+                    }
+                }
+            }))
+            .unwrap();
+
+            let expected = Frame::from_fn(width, height, pattern);
+            assert_eq!(emulator.borrow().frame(rotation).diff(&expected), []);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_solid_is_clipped_to_page() {
+        use embedded_graphics_core::{
+            draw_target::DrawTarget,
+            geometry::{Point, Size},
+            pixelcolor::BinaryColor,
+            primitives::Rectangle,
+        };
+
+        for rotation in [DisplayRotation::Rotate0, DisplayRotation::Rotate270] {
+            let (mut display, emulator): (PagedMode<_, _>, _) =
+                setup(Sh1106_128_64 {}, |builder| builder.with_rotation(rotation));
+
+            let (width, height) = display.get_dimensions();
+            block_on(display.draw(|page| {
+                let area = Rectangle::new(Point::new(20, 5), Size::new(30, 8));
+                page.fill_solid(&area, BinaryColor::On).unwrap();
+                for i in 0..width.min(height) as i32 {
+                    let dot = Rectangle::new(Point::new(i, i), Size::new(1, 1));
+                    page.fill_solid(&dot, BinaryColor::On).unwrap();
+                }
+            }))
+            .unwrap();
+
+            let expected = Frame::from_fn(width.into(), height.into(), pattern);
+            assert_eq!(emulator.borrow().frame(rotation).diff(&expected), []);
+        }
+    }
+
+    #[test]
+    fn page_buffer_is_sized_for_the_variant() {
+        use crate::displays::ssd1306::Ssd1306_72_40;
+
+        let (mut display, emulator): (PagedMode<_, _>, _) = setup(Ssd1306_72_40 {}, identity);
+        assert_eq!(display.buffer.len(), 72);
+        block_on(display.draw(|page| page.set_pixel(71, 39, 1))).unwrap();
+        assert!(emulator.borrow().pixel(71, 39));
+
+        // a wider buffer works too
+        let (mut display, emulator): (PagedMode<_, _, [u8; 160]>, _) =
+            setup(Ssd1306_72_40 {}, identity);
+        block_on(display.draw(|page| page.set_pixel(71, 39, 1))).unwrap();
+        block_on(display.draw(|page| page.set_pixel(0, 0, 1))).unwrap();
+        assert!(emulator.borrow().pixel(0, 0) && !emulator.borrow().pixel(71, 39));
+    }
+}
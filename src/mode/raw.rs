@@ -6,10 +6,20 @@
 
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::DisplayError;
 #[cfg(feature = "blocking")]
 use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
 
-use crate::{display, mode::displaymode::DisplayModeTrait, properties::DisplayProperties};
+use crate::{
+    display,
+    mode::displaymode::{sealed::NewMode, DisplayModeTrait},
+    power::{PowerControl, PowerState},
+    properties::DisplayProperties,
+};
 
 /// Raw display mode
 #[maybe_async_cfg::maybe(
@@ -35,19 +45,39 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV, DI> DisplayModeTrait<DV, DI> for RawMode<DV, DI>
+impl<DV, DI> NewMode<DV, DI> for RawMode<DV, DI>
 where
     DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
 {
     /// Create new RawMode instance
     fn new(properties: DisplayProperties<DV, DI>) -> Self {
         RawMode { properties }
     }
+}
 
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> DisplayModeTrait<DV, DI> for RawMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
     /// Release all resources used by RawMode
     fn release(self) -> DisplayProperties<DV, DI> {
         self.properties
     }
+
+    /// Initialise the display in column mode, with display RAM left as it is
+    async fn init(&mut self) -> Result<(), DisplayError> {
+        self.properties.init_column_mode().await
+    }
 }
 
 #[maybe_async_cfg::maybe(
@@ -58,9 +88,43 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DV: display::DisplayVariant, DI: AsyncWriteOnlyDataCommand> RawMode<DV, DI> {
-    /// Create a new raw display mode
-    pub fn new(properties: DisplayProperties<DV, DI>) -> Self {
-        RawMode { properties }
+impl<DV, DI> PowerControl for RawMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        self.properties.display_on(on).await
+    }
+
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let state = if sleep { PowerState::Sleep } else { PowerState::On };
+        self.properties.set_power_state(state, delay).await
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::convert::identity;
+
+    use super::RawMode;
+    use crate::{
+        displays::ssd1306::Ssd1306_128_64, power::PowerControl, sim::block_on,
+        test_helpers::setup,
+    };
+
+    #[test]
+    fn sleep_switches_charge_pump_off() {
+        let (raw, emulator): (RawMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let _sleeping = block_on(raw.sleep()).unwrap();
+
+        assert!(!emulator.borrow().display_on() && !emulator.borrow().charge_pump());
     }
 }
@@ -0,0 +1,454 @@
+//! Character cell text console with a built-in 8x8 font
+//!
+//! Text is kept as one byte per 8x8 character cell and rendered straight into display pages on
+//! `flush()`, so no framebuffer is needed. The terminal implements [`core::fmt::Write`]:
+//!
+//! ```rust,no_run
+//! use core::fmt::Write;
+//!
+//! let mut term: TerminalMode<_, _> = Builder::new(Sh1106_128_64 {})
+//!     .connect(display_interface)
+//!     .init(&mut reset, &mut delay)
+//!     .await
+//!     .unwrap();
+//!
+//! writeln!(term, "Booting...").unwrap();
+//! write!(term, "Free memory:\t{} bytes", free).unwrap();
+//! term.flush().await.unwrap();
+//! ```
+//!
+//! `\n` moves to the start of the next line, `\r` to the start of the current line and `\t` to
+//! the next multiple of four columns. When the cursor moves past the last line, the terminal
+//! scrolls up by one line. Without rotation, or rotated by 180 degrees, this is done by changing
+//! the display start line, so only the new line is sent on the next `flush()`. Rotated by 90 or
+//! 270 degrees, display RAM rows run along the lines, so the whole screen is sent again.
+
+use core::fmt;
+
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    display,
+    displayrotation::DisplayRotation,
+    font,
+    mode::displaymode::{sealed::NewMode, DisplayModeTrait},
+    power::{PowerControl, PowerState},
+    properties::DisplayProperties,
+};
+
+/// Largest number of character cells in either direction, for a 160x160 display
+const MAX_CELLS: usize = 160 / 8;
+
+/// Tab stops are placed every `TAB_WIDTH` columns
+const TAB_WIDTH: u8 = 4;
+
+/// Terminal mode handler
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub struct TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    properties: DisplayProperties<DV, DI>,
+    cells: [[u8; MAX_CELLS]; MAX_CELLS],
+    /// Bit mask of the columns of each line that changed since the last flush
+    dirty: [u32; MAX_CELLS],
+    cursor: (u8, u8),
+    /// Display RAM page shown in the first line
+    top_page: u8,
+    top_page_changed: bool,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> NewMode<DV, DI> for TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Create new TerminalMode instance
+    fn new(properties: DisplayProperties<DV, DI>) -> Self {
+        TerminalMode {
+            properties,
+            cells: [[b' '; MAX_CELLS]; MAX_CELLS],
+            dirty: [0; MAX_CELLS],
+            cursor: (0, 0),
+            top_page: 0,
+            top_page_changed: false,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> DisplayModeTrait<DV, DI> for TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Release all resources used by TerminalMode
+    fn release(self) -> DisplayProperties<DV, DI> {
+        self.properties
+    }
+
+    async fn init(&mut self) -> Result<(), DisplayError> {
+        TerminalMode::init(self).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> PowerControl for TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        TerminalMode::display_on(self, on).await
+    }
+
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let state = if sleep { PowerState::Sleep } else { PowerState::On };
+        self.properties.set_power_state(state, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    /// Clear the terminal and move the cursor to the top left corner. You need to call
+    /// `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        self.cells = [[b' '; MAX_CELLS]; MAX_CELLS];
+        self.dirty = [u32::MAX; MAX_CELLS];
+        self.cursor = (0, 0);
+    }
+
+    /// Initialise the display and clear the terminal
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        self.properties.init_column_mode().await?;
+        self.top_page = 0;
+        self.top_page_changed = false;
+        self.clear();
+        self.flush().await
+    }
+
+    /// Send the lines changed since the last flush to the display
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        let (columns, lines) = self.get_size();
+
+        match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                let ram_pages = self.ram_pages();
+                let mut buf = [0u8; MAX_CELLS * 8];
+
+                for line in 0..lines {
+                    let dirty = self.dirty[line as usize] & column_mask(columns);
+                    if dirty == 0 {
+                        continue;
+                    }
+                    // send the changed part of the line in one go
+                    let first = dirty.trailing_zeros() as usize;
+                    let last = 31 - dirty.leading_zeros() as usize;
+                    let buf = &mut buf[..(last - first + 1) * 8];
+                    for (cell, glyph) in buf.chunks_exact_mut(8).enumerate() {
+                        glyph
+                            .copy_from_slice(&font::glyph(self.cells[line as usize][first + cell]));
+                    }
+
+                    let page = (line + self.top_page) % ram_pages;
+                    self.properties
+                        .draw_page(page, first as u8 * 8, buf)
+                        .await?;
+                }
+
+                if self.top_page_changed {
+                    self.properties.set_start_line(self.top_page * 8).await?;
+                    self.top_page_changed = false;
+                }
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                // lines run down display RAM columns, so every cell is a separate page write
+                for line in 0..lines {
+                    for column in 0..columns {
+                        if self.dirty[line as usize] & (1 << column) == 0 {
+                            continue;
+                        }
+                        let glyph =
+                            transpose(font::glyph(self.cells[line as usize][column as usize]));
+                        self.properties.draw_page(column, line * 8, &glyph).await?;
+                    }
+                }
+            }
+        }
+
+        self.dirty = [0; MAX_CELLS];
+
+        Ok(())
+    }
+
+    /// Write a character at the cursor and advance the cursor. You need to call
+    /// `display.flush()` for any effect on the screen
+    pub fn print_char(&mut self, c: char) {
+        let columns = self.get_size().0;
+
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.cursor.0 = 0,
+            '\t' => {
+                let stop = (self.cursor.0 / TAB_WIDTH + 1) * TAB_WIDTH;
+                while self.cursor.0 < stop.min(columns) {
+                    self.put(b' ');
+                }
+            }
+            c if c.is_control() => {}
+            c => {
+                // wrap only once there is something to print on the next line
+                if self.cursor.0 >= columns {
+                    self.new_line();
+                }
+                self.put(if c.is_ascii() { c as u8 } else { b'?' });
+            }
+        }
+    }
+
+    /// Get the number of character columns and lines, taking into account the current rotation
+    /// of the display
+    pub fn get_size(&self) -> (u8, u8) {
+        let (width, height) = self.properties.get_dimensions();
+
+        (width / 8, height / 8)
+    }
+
+    /// Get the cursor position as column and line
+    pub fn get_position(&self) -> (u8, u8) {
+        self.cursor
+    }
+
+    /// Move the cursor to `column` and `line`
+    pub fn set_position(&mut self, column: u8, line: u8) -> Result<(), DisplayError> {
+        let (columns, lines) = self.get_size();
+        if column >= columns || line >= lines {
+            return Err(DisplayError::OutOfBoundsError);
+        }
+        self.cursor = (column, line);
+
+        Ok(())
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        self.properties.display_on(on).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+        self.properties.set_contrast(contrast).await
+    }
+
+    /// Store a character at the cursor and advance the cursor
+    fn put(&mut self, c: u8) {
+        let (column, line) = self.cursor;
+        self.cells[line as usize][column as usize] = c;
+        self.dirty[line as usize] |= 1 << column;
+        self.cursor.0 += 1;
+    }
+
+    /// Move the cursor to the start of the next line, scrolling up at the bottom
+    fn new_line(&mut self) {
+        let lines = self.get_size().1;
+
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < lines {
+            self.cursor.1 += 1;
+            return;
+        }
+
+        let last = lines as usize - 1;
+        self.cells.copy_within(1..=last, 0);
+        self.cells[last] = [b' '; MAX_CELLS];
+        match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                // the other lines stay in display RAM, their pending changes move up with them
+                self.dirty.copy_within(1..=last, 0);
+                self.dirty[last] = u32::MAX;
+                self.top_page = (self.top_page + 1) % self.ram_pages();
+                self.top_page_changed = true;
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                self.dirty = [u32::MAX; MAX_CELLS];
+            }
+        }
+    }
+
+    /// Number of pages in display RAM the start line wraps around, controllers for displays of
+    /// up to 64 rows have 64 rows of display RAM
+    fn ram_pages(&self) -> u8 {
+        self.properties.get_size().1.max(64) / 8
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> fmt::Write for TerminalMode<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: display::DisplayVariant,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().for_each(|c| self.print_char(c));
+
+        Ok(())
+    }
+}
+
+/// Bit mask of the first `columns` columns of a line
+fn column_mask(columns: u8) -> u32 {
+    u32::MAX.checked_shr(32 - columns as u32).unwrap_or(0)
+}
+
+/// Turn a glyph by 90 degrees, so that its columns run along display RAM rows
+fn transpose(glyph: [u8; 8]) -> [u8; 8] {
+    let mut out = [0; 8];
+    for (i, column) in glyph.iter().enumerate() {
+        for (j, row) in out.iter_mut().enumerate() {
+            *row |= ((column >> j) & 1) << i;
+        }
+    }
+
+    out
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::{convert::identity, fmt::Write};
+
+    use super::TerminalMode;
+    use crate::{
+        displays::ssd1306::Ssd1306_128_64, font, prelude::*, screenshot::Frame, sim::block_on,
+        test_helpers::setup,
+    };
+
+    /// Check that the character cell at `column`, `line` of `frame` shows `c`
+    fn shows(frame: &Frame, column: u32, line: u32, c: u8) -> bool {
+        let glyph = font::glyph(c);
+
+        (0..8).all(|x| {
+            (0..8).all(|y| {
+                frame.pixel(column * 8 + x, line * 8 + y) == (glyph[x as usize] >> y & 1 != 0)
+            })
+        })
+    }
+
+    #[test]
+    fn prints_text_at_cursor() {
+        let (mut term, emulator): (TerminalMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        write!(term, "Hi\n\tx\ry").unwrap();
+        assert_eq!(term.get_position(), (1, 1));
+        block_on(term.flush()).unwrap();
+
+        let frame = emulator.borrow().frame(DisplayRotation::Rotate0);
+        assert!(shows(&frame, 0, 0, b'H'));
+        assert!(shows(&frame, 1, 0, b'i'));
+        assert!(shows(&frame, 0, 1, b'y'));
+        assert!(shows(&frame, 4, 1, b'x'));
+    }
+
+    #[test]
+    fn scrolls_with_start_line() {
+        let (mut term, emulator): (TerminalMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        for c in b'A'..=b'J' {
+            writeln!(term, "{}", c as char).unwrap();
+        }
+        block_on(term.flush()).unwrap();
+
+        assert_eq!(emulator.borrow().start_line(), 24);
+        let frame = emulator.borrow().frame(DisplayRotation::Rotate0);
+        assert!(shows(&frame, 0, 0, b'D'));
+        assert!(shows(&frame, 0, 6, b'J'));
+        assert!(shows(&frame, 0, 7, b' '));
+    }
+
+    #[test]
+    fn rotated_terminal_reads_upright() {
+        let (mut term, emulator): (TerminalMode<_, _>, _) = setup(Ssd1306_128_64 {}, |builder| {
+            builder.with_rotation(DisplayRotation::Rotate90)
+        });
+
+        assert_eq!(term.get_size(), (8, 16));
+        for c in b'A'..=b'Q' {
+            writeln!(term, "{}{}", c as char, c as char).unwrap();
+        }
+        block_on(term.flush()).unwrap();
+
+        let frame = emulator.borrow().frame(DisplayRotation::Rotate90);
+        assert!(shows(&frame, 0, 0, b'C'));
+        assert!(shows(&frame, 1, 14, b'Q'));
+        assert!(shows(&frame, 0, 15, b' '));
+    }
+}
@@ -0,0 +1,255 @@
+//! Display power states
+//!
+//! A display mode returned by [`UninitDisplay::init`](crate::builder::UninitDisplay::init) is
+//! active. [`PowerControl::sleep`] turns the panel off and returns it as [`Sleeping`], which
+//! can't be drawn to until [`Sleeping::wake`] turns the panel on again:
+//!
+//! ```rust,no_run
+//! let sleeping = display.sleep().await?;
+//! // ...
+//! let mut display = sleeping.wake(&mut delay).await?;
+//! ```
+//!
+//! Waking up switches the charge pump on and waits on `delay` for it to settle before the panel
+//! is turned on. If a command can't be sent, the display is handed back in its previous state
+//! inside the [`PowerError`], so a failed transition doesn't lose the interface.
+//!
+//! Displays can also be switched between the [`PowerState`]s in place, with
+//! [`GraphicsMode::set_power_state`](crate::mode::GraphicsMode::set_power_state). This is for
+//! code that only borrows the display, like the screensaver of the [burn-in
+//! protection](crate::burnin), and for [`PowerState::PowerDown`], e.g. before removing the supply
+//! of the display. The buffer can still be drawn to in any state, but nothing is sent to a
+//! powered down display until it wakes up. Prefer the typestate where the display is owned, it
+//! rules out drawing to a sleeping display at compile time.
+
+use core::fmt;
+
+use display_interface::DisplayError;
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
+
+/// Power state of a display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// The panel is on
+    On,
+    /// The panel and the charge pump are off. Display RAM is kept and can still be written to.
+    Sleep,
+    /// Asleep, ready for the supply of the display to be removed. Display RAM is assumed to be
+    /// lost, so the display is initialised again when it wakes up.
+    PowerDown,
+}
+
+/// Display modes that can turn the panel on and off
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+pub trait PowerControl: Sized {
+    /// Turn the panel on or off. Display RAM is kept while the panel is off.
+    #[allow(async_fn_in_trait)]
+    async fn display_on(&mut self, on: bool) -> Result<(), DisplayError>;
+
+    /// Put the display to sleep or wake it up. Defaults to turning the panel off and on, display
+    /// modes of a [`DisplayVariant`](crate::display::DisplayVariant) also switch the charge pump
+    /// and wait on the delay for it to settle when waking up.
+    #[allow(async_fn_in_trait)]
+    async fn set_sleep<DELAY>(
+        &mut self,
+        sleep: bool,
+        _delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        self.display_on(!sleep).await
+    }
+
+    /// Turn the panel off and put the display to sleep
+    #[allow(async_fn_in_trait)]
+    async fn sleep(mut self) -> Result<Sleeping<Self>, PowerError<Self>> {
+        match self.set_sleep(true, &mut NoDelay).await {
+            Ok(()) => Ok(Sleeping { display: self }),
+            Err(error) => Err(PowerError {
+                display: self,
+                error,
+            }),
+        }
+    }
+}
+
+/// A display that has been put to sleep with [`PowerControl::sleep`]
+pub struct Sleeping<MODE> {
+    display: MODE,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<MODE> Sleeping<MODE>
+where
+    MODE: PowerControl,
+{
+    /// Turn the panel on again and return the active display. `delay` waits for the charge pump
+    /// to settle.
+    pub async fn wake<DELAY>(self, delay: &mut DELAY) -> Result<MODE, PowerError<Self>>
+    where
+        DELAY: DelayNs,
+    {
+        let mut display = self.display;
+        match display.set_sleep(false, delay).await {
+            Ok(()) => Ok(display),
+            Err(error) => Err(PowerError {
+                display: Sleeping { display },
+                error,
+            }),
+        }
+    }
+}
+
+/// Delay for the transitions that never wait, like going to sleep
+pub(crate) struct NoDelay;
+
+#[maybe_async_cfg::maybe(
+    sync(feature = "blocking", keep_self),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Failed power state change, with the display in the state it was in before
+pub struct PowerError<T> {
+    /// The display, as it was before the change
+    pub display: T,
+    /// The error returned by the display interface
+    pub error: DisplayError,
+}
+
+impl<T> fmt::Debug for PowerError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PowerError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> From<PowerError<T>> for DisplayError {
+    fn from(error: PowerError<T>) -> Self {
+        error.error
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::{cell::RefCell, convert::identity};
+
+    use super::{DelayNs, NoDelay};
+    use crate::{
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_64},
+        prelude::*,
+        sim::{block_on, Emulator},
+        test_helpers::setup,
+    };
+
+    #[test]
+    fn sleep_and_wake_keep_the_image() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        display.set_pixel(7, 9, 1);
+        block_on(display.flush()).unwrap();
+        let sleeping = block_on(display.sleep()).unwrap();
+        let display = block_on(sleeping.wake(&mut NoDelay)).unwrap();
+
+        assert_eq!(display.get_dimensions(), (128, 64));
+        assert!(emulator.borrow().display_on() && emulator.borrow().pixel(7, 9));
+
+        let _sleeping = block_on(display.sleep()).unwrap();
+
+        assert!(!emulator.borrow().display_on() && !emulator.borrow().charge_pump());
+    }
+
+    #[test]
+    fn sleep_switches_charge_pump_off() {
+        /// Delay logging the time waited and whether the charge pump was on with the panel off
+        struct Settle<'a> {
+            emulator: &'a RefCell<Emulator>,
+            waited: u64,
+            settling: bool,
+        }
+
+        #[maybe_async_cfg::maybe(
+            sync(feature = "blocking", keep_self),
+            async(not(feature = "blocking"), keep_self)
+        )]
+        impl DelayNs for Settle<'_> {
+            async fn delay_ns(&mut self, ns: u32) {
+                let emulator = self.emulator.borrow();
+                self.waited += ns as u64;
+                self.settling = emulator.charge_pump() && !emulator.display_on();
+            }
+        }
+
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let mut delay = Settle {
+            emulator: &emulator,
+            waited: 0,
+            settling: false,
+        };
+
+        display.set_pixel(7, 9, 1);
+        block_on(display.flush()).unwrap();
+        assert!(emulator.borrow().charge_pump());
+
+        block_on(display.set_power_state(PowerState::Sleep, &mut delay)).unwrap();
+        assert!(!emulator.borrow().display_on() && !emulator.borrow().charge_pump());
+        assert!(emulator.borrow().ram_pixel(7, 9));
+        assert_eq!(delay.waited, 0);
+
+        block_on(display.set_power_state(PowerState::On, &mut delay)).unwrap();
+        assert!(emulator.borrow().charge_pump() && emulator.borrow().pixel(7, 9));
+        assert_eq!(display.get_power_state(), PowerState::On);
+        // the panel is turned on once the charge pump has settled
+        assert!(delay.settling && delay.waited >= 100_000_000);
+    }
+
+    #[test]
+    fn wake_from_power_down_reinitialises_and_reflushes() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Sh1106_128_64 {}, |builder| {
+            builder.with_rotation(DisplayRotation::Rotate90)
+        });
+
+        display.set_reflush_on_wake(true);
+        block_on(display.set_scroll_offset(3)).unwrap();
+        display.set_pixel(5, 100, 1);
+        block_on(display.flush()).unwrap();
+        block_on(display.set_power_state(PowerState::PowerDown, &mut NoDelay)).unwrap();
+        assert!(!emulator.borrow().display_on() && !emulator.borrow().charge_pump());
+        let sent = emulator.borrow().transactions();
+
+        // nothing is sent to a powered down display
+        display.set_pixel(6, 100, 1);
+        block_on(display.flush()).unwrap();
+        assert_eq!(emulator.borrow().transactions(), sent);
+
+        emulator.borrow_mut().power_cycle();
+        block_on(display.set_power_state(PowerState::On, &mut NoDelay)).unwrap();
+
+        let emulator = emulator.borrow();
+        assert!(emulator.display_on() && emulator.charge_pump());
+        assert_eq!(emulator.start_line(), 3);
+        let frame = emulator.frame(DisplayRotation::Rotate90);
+        assert_eq!(frame.diff(&display.frame()), []);
+    }
+}
@@ -1,3 +1,3 @@
 //! Crate prelude
 
-pub use super::{displayrotation::DisplayRotation, mode::GraphicsMode};
+pub use super::{displayrotation::{DisplayRotation, Mirror}, mode::{GraphicsMode, GrayscaleMode, PagedMode, TerminalMode}, power::{PowerControl, PowerState}};
@@ -6,14 +6,27 @@ use display_interface::AsyncWriteOnlyDataCommand;
 use display_interface::WriteOnlyDataCommand;
 
 use display_interface::{DataFormat, DisplayError};
+#[cfg(feature = "blocking")]
+use hal::delay::DelayNs;
+#[cfg(not(feature = "blocking"))]
+use embedded_hal_async::delay::DelayNs;
 
-use crate::{command::Command, display::DisplayVariant, displayrotation::DisplayRotation};
+use crate::{
+    command::Command,
+    display::{DisplayVariant, GrayscaleDisplayVariant, InitConfig},
+    displayrotation::{DisplayRotation, Mirror},
+    power::PowerState,
+    scroll::ScrollConfig,
+};
 
 /// Display properties struct
 pub struct DisplayProperties<DV, DI> {
-    _variant: DV,
+    variant: DV,
     iface: DI,
     display_rotation: DisplayRotation,
+    mirror: Mirror,
+    init_config: Option<InitConfig>,
+    power_state: PowerState,
     draw_area_start: (u8, u8),
     draw_area_end: (u8, u8),
 }
@@ -29,33 +42,142 @@ pub struct DisplayProperties<DV, DI> {
 impl<DV, DI> DisplayProperties<DV, DI>
 where
     DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
 {
     /// Create new DisplayProperties instance
-    pub fn new(
+    pub(crate) fn new(
         variant: DV,
         iface: DI,
         display_rotation: DisplayRotation,
     ) -> DisplayProperties<DV, DI> {
         DisplayProperties {
-            _variant: variant,
+            variant,
             iface,
             display_rotation,
+            mirror: Mirror::None,
+            init_config: None,
+            power_state: PowerState::On,
             draw_area_start: (0, 0),
             draw_area_end: (0, 0),
         }
     }
 
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Get the display mirroring
+    pub fn get_mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Set the display mirroring, applied on top of the rotation. Takes effect on the next call
+    /// to `init_column_mode` or `set_rotation`.
+    pub fn set_mirror(&mut self, mirror: Mirror) {
+        self.mirror = mirror;
+    }
+
+    /// Override the initialisation parameters of the display variant. Takes effect on the next
+    /// call to `init_column_mode`.
+    pub fn set_init_config(&mut self, config: InitConfig) {
+        self.init_config = Some(config);
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+        Command::DisplayOn(on).send(&mut self.iface).await
+    }
+
+    /// Get the power state set with `set_power_state`
+    pub fn get_power_state(&self) -> PowerState {
+        self.power_state
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
+        Command::Contrast(contrast).send(&mut self.iface).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> DisplayProperties<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: DisplayVariant,
+{
     /// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
     /// column 0 on the left and column _(display_width - 1)_ on the right.
     pub async fn init_column_mode(&mut self) -> Result<(), DisplayError> {
         let display_rotation = self.display_rotation;
-        DV::init_column_mode(&mut self.iface).await?;
+        let config = self.get_init_config();
+        self.variant
+            .init_column_mode(&mut self.iface, &config)
+            .await?;
+        self.power_state = PowerState::On;
         self.set_rotation(display_rotation).await?;
 
         Ok(())
     }
 
+    /// Put the display to sleep, power it down or wake it up, with the sequence of the display
+    /// variant.
+    ///
+    /// Waking up from [`PowerState::Sleep`] waits on `delay` for the charge pump to settle.
+    /// Waking up from [`PowerState::PowerDown`] initialises the display again, which also
+    /// restores the rotation, mirroring and init config. Display RAM has to be rewritten then.
+    pub async fn set_power_state<DELAY>(
+        &mut self,
+        state: PowerState,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let config = self.get_init_config();
+
+        match (self.power_state, state) {
+            (from, to) if from == to => {}
+            (PowerState::On, _) => {
+                self.variant
+                    .set_sleep(&mut self.iface, true, &config, delay)
+                    .await?
+            }
+            (PowerState::Sleep, PowerState::On) => {
+                self.variant
+                    .set_sleep(&mut self.iface, false, &config, delay)
+                    .await?
+            }
+            // already asleep, only the bookkeeping changes
+            (PowerState::Sleep, _) => {}
+            (PowerState::PowerDown, _) => {
+                self.init_column_mode().await?;
+                if state == PowerState::Sleep {
+                    self.variant
+                        .set_sleep(&mut self.iface, true, &config, delay)
+                        .await?;
+                }
+            }
+        }
+        self.power_state = state;
+
+        Ok(())
+    }
+
+    /// Get the initialisation parameters, either set with `set_init_config` or the defaults of the
+    /// display variant
+    pub fn get_init_config(&self) -> InitConfig {
+        self.init_config
+            .unwrap_or_else(|| self.variant.default_init_config())
+    }
+
     /// Set the position in the framebuffer of the display where any sent data should be
     /// drawn. 
     ///
@@ -94,31 +216,34 @@ where
         start_col: u8,
         buf: &[u8],
     ) -> Result<(), DisplayError> {
-        let start_col = start_col + DV::COLUMN_OFFSET;
-        // set page/column addresses
-        for cmd in [
-            if DV::LARGE_PAGE_ADDRESS {
-                Command::LargePageAddress(page_addr)
-            } else {
-                Command::PageAddress(page_addr)
-            },
-            Command::ColumnAddressLow(0xF & start_col),
-            Command::ColumnAddressHigh(0xF & (start_col >> 4)),
-        ] {
-            cmd.send(&mut self.iface).await?;
-        }
+        let start_col = start_col + self.variant.column_offset();
+        // set page/column addresses in one transaction
+        let page = if self.variant.large_page_address() {
+            Command::LargePageAddress(page_addr)
+        } else {
+            Command::PageAddress(page_addr)
+        };
+        Command::send_batch(
+            [
+                page,
+                Command::ColumnAddressLow(0xF & start_col),
+                Command::ColumnAddressHigh(0xF & (start_col >> 4)),
+            ],
+            &mut self.iface,
+        )
+        .await?;
 
         self.iface.send_data(DataFormat::U8(buf)).await
     }
 
-    // Get the configured display size
-    //pub fn get_size(&self) -> DisplaySize {
-    //    self.display_size
-    //}
+    /// Get the configured display size, ignoring the rotation of the display
+    pub fn get_size(&self) -> (u8, u8) {
+        (self.variant.width(), self.variant.height())
+    }
 
     /// Get display dimensions, taking into account the current rotation of the display
     pub fn get_dimensions(&self) -> (u8, u8) {
-        let (w, h) = DV::dimensions();
+        let (w, h) = self.get_size();
 
         match self.display_rotation {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
@@ -126,46 +251,145 @@ where
         }
     }
 
-    /// Get the display rotation
-    pub fn get_rotation(&self) -> DisplayRotation {
-        self.display_rotation
+    /// Set the display start line, i.e. the row of display RAM shown in the first row of the
+    /// display
+    pub async fn set_start_line(&mut self, line: u8) -> Result<(), DisplayError> {
+        // the single byte command only covers 64 rows
+        if self.variant.height() > 64 {
+            Command::LargeStartLine(line).send(&mut self.iface).await
+        } else {
+            Command::StartLine(line).send(&mut self.iface).await
+        }
     }
 
-    /// Set the display rotation
+    /// Set up and start a continuous hardware scroll. Only supported by SSD130x controllers.
+    pub async fn start_scroll(&mut self, config: ScrollConfig) -> Result<(), DisplayError> {
+        // scroll parameters must not be changed while scrolling
+        Command::EnableScroll(false).send(&mut self.iface).await?;
+        self.variant.setup_scroll(&mut self.iface, &config).await?;
+        Command::EnableScroll(true).send(&mut self.iface).await
+    }
+
+    /// Stop a hardware scroll. The display RAM has to be rewritten afterwards.
+    pub async fn stop_scroll(&mut self) -> Result<(), DisplayError> {
+        Command::EnableScroll(false).send(&mut self.iface).await
+    }
+
+    /// Set the display rotation, combined with the mirroring set with `set_mirror`
     pub async fn set_rotation(
         &mut self,
         display_rotation: DisplayRotation,
     ) -> Result<(), DisplayError> {
         self.display_rotation = display_rotation;
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate90 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate180 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
+        let (segment_remap, reverse_com_dir) = match display_rotation {
+            DisplayRotation::Rotate0 => (true, true),
+            DisplayRotation::Rotate90 => (false, true),
+            DisplayRotation::Rotate180 => (false, false),
+            DisplayRotation::Rotate270 => (true, false),
+        };
+        // the x axis of a quarter turned image runs along the COM lines
+        let (flip_x, flip_y) = match (display_rotation, self.mirror.flips()) {
+            (DisplayRotation::Rotate0 | DisplayRotation::Rotate180, flips) => flips,
+            (DisplayRotation::Rotate90 | DisplayRotation::Rotate270, (x, y)) => (y, x),
+        };
+
+        Command::send_batch(
+            [
+                Command::SegmentRemap(segment_remap ^ flip_x),
+                Command::ReverseComDir(reverse_com_dir ^ flip_y),
+            ],
+            &mut self.iface,
+        )
+        .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl<DV, DI> DisplayProperties<DV, DI>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    DV: GrayscaleDisplayVariant,
+{
+    /// Initialise a grayscale display
+    pub async fn init_grayscale(&mut self) -> Result<(), DisplayError> {
+        let config = self.init_config.unwrap_or_else(DV::default_init_config);
+        DV::init(&mut self.iface, &config).await?;
+        self.power_state = PowerState::On;
+
+        Ok(())
+    }
+
+    /// Put a grayscale display to sleep, power it down or wake it up, like `set_power_state`
+    /// does for the monochrome displays. Waking up from [`PowerState::PowerDown`] initialises
+    /// the display again, display RAM has to be rewritten then.
+    pub async fn set_grayscale_power_state<DELAY>(
+        &mut self,
+        state: PowerState,
+        delay: &mut DELAY,
+    ) -> Result<(), DisplayError>
+    where
+        DELAY: DelayNs,
+    {
+        let config = self.init_config.unwrap_or_else(DV::default_init_config);
+
+        match (self.power_state, state) {
+            (from, to) if from == to => {}
+            (PowerState::On, _) => DV::set_sleep(&mut self.iface, true, &config, delay).await?,
+            (PowerState::Sleep, PowerState::On) => {
+                DV::set_sleep(&mut self.iface, false, &config, delay).await?
             }
-            DisplayRotation::Rotate270 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
+            // already asleep, only the bookkeeping changes
+            (PowerState::Sleep, _) => {}
+            (PowerState::PowerDown, _) => {
+                self.init_grayscale().await?;
+                if state == PowerState::Sleep {
+                    DV::set_sleep(&mut self.iface, true, &config, delay).await?;
+                }
             }
         }
+        self.power_state = state;
+
+        Ok(())
     }
 
-    /// Turn the display on or off. The display can be drawn to and retains all
-    /// of its memory even while off.
-    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
-        Command::DisplayOn(on).send(&mut self.iface).await
+    /// Send a window of nibble packed pixel data to a grayscale display, starting at column `x`
+    /// and row `y`. See [`GrayscaleDisplayVariant::draw_window`] for the expected layout.
+    pub async fn draw_window(
+        &mut self,
+        x: u16,
+        y: u16,
+        width: u16,
+        buf: &[u8],
+    ) -> Result<(), DisplayError> {
+        DV::draw_window(&mut self.iface, x, y, width, buf).await
     }
+}
 
-    /// Set the display contrast
-    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
-        Command::Contrast(contrast).send(&mut self.iface).await
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::convert::identity;
+
+    use crate::{displays::sh1108::Sh1108_64_160, prelude::*, sim::block_on, test_helpers::setup};
+
+    #[test]
+    fn page_transfer_is_one_command_transaction() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Sh1108_64_160 {}, identity);
+
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(20, 159, 1);
+        block_on(display.flush()).unwrap();
+
+        // the init sequence, the variant specific settings and the rotation, then one per page
+        let emulator = emulator.borrow();
+        assert_eq!(emulator.transactions(), (5, 2));
+        assert!(emulator.pixel(10, 0) && emulator.pixel(20, 159));
     }
 }
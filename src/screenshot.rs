@@ -0,0 +1,323 @@
+//! Monochrome frames for screenshots and golden image tests
+//!
+//! A [`Frame`] is taken from [`GraphicsMode::frame`](crate::mode::GraphicsMode::frame) or
+//! [`Emulator::frame`](crate::sim::Emulator::frame) and can be written as PBM or PNG. Reference
+//! images are stored as PBM, which [`Frame::read_pbm`] loads again for comparison:
+//!
+//! ```rust,no_run
+//! use std::fs::File;
+//! use oled_async::screenshot::Frame;
+//!
+//! let frame = display.frame();
+//! frame.write_png(File::create("screenshot.png")?)?;
+//!
+//! let reference = Frame::read_pbm(File::open("tests/reference/hello.pbm")?)?;
+//! assert_eq!(frame.diff(&reference), []);
+//! ```
+//!
+//! Lit pixels are stored as white in both formats, so images look like the panel. Only available
+//! with the `std` feature.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    vec,
+    vec::Vec,
+};
+
+/// Image of a monochrome display, `true` for lit pixels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: u32,
+    height: u32,
+    pixels: Vec<bool>,
+}
+
+impl Frame {
+    /// Create a new frame with all pixels dark
+    pub fn new(width: u32, height: u32) -> Self {
+        Frame {
+            width,
+            height,
+            pixels: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Create a new frame, calling `pixel` with the coordinates of each pixel to get its value
+    pub fn from_fn(width: u32, height: u32, mut pixel: impl FnMut(u32, u32) -> bool) -> Self {
+        let mut frame = Frame::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                frame.set_pixel(x, y, pixel(x, y));
+            }
+        }
+
+        frame
+    }
+
+    /// Get the size of the frame in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Get whether the pixel at `x`, `y` is lit. Pixels outside of the frame are dark.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.pixels[self.index(x, y)]
+    }
+
+    /// Set the pixel at `x`, `y`. If the coordinates are out of the bounds of the frame, this
+    /// method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, lit: bool) {
+        if x < self.width && y < self.height {
+            let idx = self.index(x, y);
+            self.pixels[idx] = lit;
+        }
+    }
+
+    /// Get the coordinates of all pixels that differ from `reference`, row by row. If the sizes
+    /// differ, the frames are compared over the larger size with missing pixels taken as dark.
+    pub fn diff(&self, reference: &Frame) -> Vec<(u32, u32)> {
+        let width = self.width.max(reference.width);
+        let height = self.height.max(reference.height);
+
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.pixel(x, y) != reference.pixel(x, y))
+            .collect()
+    }
+
+    /// Write the frame as a binary (P4) PBM image
+    pub fn write_pbm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P4\n{} {}\n", self.width, self.height)?;
+        // PBM uses 1 for black
+        writer.write_all(&self.pack_rows(false))
+    }
+
+    /// Write the frame as a 1 bit grayscale PNG image
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // bit depth 1, grayscale, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[1, 0, 0, 0, 0]);
+
+        // every scanline starts with the filter type, 0 for none
+        let row_bytes = self.width.div_ceil(8) as usize;
+        let mut scanlines = Vec::with_capacity((row_bytes + 1) * self.height as usize);
+        for row in self.pack_rows(true).chunks(row_bytes.max(1)) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_png_chunk(&mut writer, b"IHDR", &header)?;
+        write_png_chunk(&mut writer, b"IDAT", &zlib_stored(&scanlines))?;
+        write_png_chunk(&mut writer, b"IEND", &[])
+    }
+
+    /// Read a PBM image in plain (P1) or binary (P4) format, as written by [`Frame::write_pbm`]
+    pub fn read_pbm<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+
+        let magic = read_pbm_token(&mut reader)?;
+        let width = parse_pbm_number(&read_pbm_token(&mut reader)?)?;
+        let height = parse_pbm_number(&read_pbm_token(&mut reader)?)?;
+
+        let mut frame = Frame::new(width, height);
+        match magic.as_slice() {
+            b"P1" => {
+                let mut bits = reader
+                    .bytes()
+                    .filter(|byte| !matches!(byte, Ok(b) if b.is_ascii_whitespace()));
+                for y in 0..height {
+                    for x in 0..width {
+                        match bits.next().transpose()? {
+                            Some(b'0') => frame.set_pixel(x, y, true),
+                            Some(b'1') => frame.set_pixel(x, y, false),
+                            _ => return Err(invalid_data("truncated or invalid PBM data")),
+                        }
+                    }
+                }
+            }
+            b"P4" => {
+                let row_bytes = width.div_ceil(8) as usize;
+                let mut row = vec![0; row_bytes];
+                for y in 0..height {
+                    reader.read_exact(&mut row)?;
+                    for x in 0..width {
+                        let black = row[x as usize / 8] & (0x80 >> (x % 8)) != 0;
+                        frame.set_pixel(x, y, !black);
+                    }
+                }
+            }
+            _ => return Err(invalid_data("not a PBM image")),
+        }
+
+        Ok(frame)
+    }
+
+    /// Pack the frame into the page major layout of display RAM, 8 rows to a byte with the top one
+    /// in bit 0, to store as a [`PageImage`](crate::bitmap::PageImage)
+    pub fn to_page_major(&self) -> Vec<u8> {
+        let mut data = vec![0; self.width as usize * self.height.div_ceil(8) as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.pixel(x, y) {
+                    data[(y / 8 * self.width + x) as usize] |= 1 << (y % 8);
+                }
+            }
+        }
+
+        data
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Pack rows of pixels MSB first into bytes, each row padded to a whole byte
+    fn pack_rows(&self, lit: bool) -> Vec<u8> {
+        let row_bytes = self.width.div_ceil(8) as usize;
+        let mut data = vec![0; row_bytes * self.height as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.pixel(x, y) == lit {
+                    data[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+
+        data
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Read a whitespace separated header token, skipping comments. Consumes the single whitespace
+/// character after the token, which ends the header before binary data.
+fn read_pbm_token<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut token = Vec::new();
+    let mut byte = [0];
+
+    loop {
+        reader.read_exact(&mut byte)?;
+        match byte[0] {
+            b'#' if token.is_empty() => {
+                reader.read_until(b'\n', &mut Vec::new())?;
+            }
+            b if b.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            }
+            b => token.push(b),
+        }
+    }
+}
+
+fn parse_pbm_number(token: &[u8]) -> io::Result<u32> {
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_data("invalid PBM size"))
+}
+
+fn write_png_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+
+    let crc = !crc32(crc32(!0, kind), data);
+    writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// Wrap `data` in a zlib stream of uncompressed deflate blocks, screenshots are small enough
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        stream.push(blocks.peek().is_none() as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::{crc32, Frame};
+
+    fn checkerboard() -> Frame {
+        Frame::from_fn(11, 5, |x, y| (x + y) % 2 == 0)
+    }
+
+    #[test]
+    fn pbm_round_trip() {
+        let frame = checkerboard();
+        let mut pbm = Vec::new();
+        frame.write_pbm(&mut pbm).unwrap();
+
+        assert!(pbm.starts_with(b"P4\n11 5\n"));
+        assert_eq!(Frame::read_pbm(pbm.as_slice()).unwrap(), frame);
+    }
+
+    #[test]
+    fn read_plain_pbm_with_comment() {
+        let pbm = b"P1\n# lit corners\n3 2\n0 1 1\n1 1 0\n";
+        let frame = Frame::read_pbm(&pbm[..]).unwrap();
+
+        assert_eq!(frame.size(), (3, 2));
+        assert_eq!(frame.diff(&Frame::new(3, 2)), [(0, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn page_major_packing() {
+        let frame = Frame::from_fn(3, 10, |x, y| x == 1 || y == 9);
+
+        assert_eq!(frame.to_page_major(), [0x00, 0xFF, 0x00, 0x02, 0x03, 0x02]);
+    }
+
+    #[test]
+    fn png_chunks_are_well_formed() {
+        let mut png = Vec::new();
+        checkerboard().write_png(&mut png).unwrap();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 11, 0, 0, 0, 5]);
+        // IEND chunk has a well known CRC
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+        assert_eq!(!crc32(!0, b"123456789"), 0xCBF4_3926);
+    }
+}
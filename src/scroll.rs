@@ -0,0 +1,57 @@
+//! Hardware scrolling
+//!
+//! SSD1306 and SSD1309 controllers can scroll the displayed image continuously without any
+//! further bus traffic. Set up a scroll with
+//! [`GraphicsMode::start_scroll`](crate::mode::GraphicsMode::start_scroll):
+//!
+//! ```rust,no_run
+//! use oled_async::scroll::{HScrollDir, NFrames, ScrollConfig};
+//!
+//! display
+//!     .start_scroll(ScrollConfig::Horizontal {
+//!         direction: HScrollDir::RightToLeft,
+//!         start_page: 2,
+//!         end_page: 3,
+//!         interval: NFrames::F5,
+//!     })
+//!     .await
+//!     .unwrap();
+//! ```
+//!
+//! Page numbers are in display RAM, i.e. not affected by the display rotation.
+
+pub use crate::command::{HScrollDir, NFrames, VHScrollDir};
+
+/// Continuous scroll setup
+#[derive(Debug, Clone, Copy)]
+pub enum ScrollConfig {
+    /// Scroll pages `start_page..=end_page` horizontally, one column every `interval`
+    Horizontal {
+        /// Scroll direction
+        direction: HScrollDir,
+        /// First page to scroll
+        start_page: u8,
+        /// Last page to scroll
+        end_page: u8,
+        /// Time between scroll steps
+        interval: NFrames,
+    },
+    /// Scroll pages `start_page..=end_page` horizontally and the vertical scroll area by
+    /// `vertical_offset` rows every `interval`
+    Diagonal {
+        /// Scroll direction
+        direction: VHScrollDir,
+        /// First page to scroll horizontally
+        start_page: u8,
+        /// Last page to scroll horizontally
+        end_page: u8,
+        /// Time between scroll steps
+        interval: NFrames,
+        /// Rows to scroll vertically per step
+        vertical_offset: u8,
+        /// Number of fixed rows at the top of the display
+        fixed_rows: u8,
+        /// Number of rows in the vertical scroll area
+        scroll_rows: u8,
+    },
+}
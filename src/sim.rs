@@ -0,0 +1,965 @@
+//! Host-side emulation of a monochrome display controller
+//!
+//! [`Emulator`] implements the display interface trait and decodes the command and data bytes
+//! sent by the driver, so that the image a panel would show can be checked without hardware:
+//!
+//! ```rust,no_run
+//! use oled_async::{displays::sh1106::Sh1106_128_64, prelude::*, sim::Emulator, Builder};
+//!
+//! let variant = Sh1106_128_64 {};
+//! let mut emulator = Emulator::new(&variant);
+//! let uninit = Builder::new(variant).connect(&mut emulator);
+//! let mut display: GraphicsMode<_, _> = uninit.init_without_reset().await.unwrap();
+//!
+//! display.set_pixel(3, 5, 1);
+//! display.flush().await.unwrap();
+//!
+//! assert!(emulator.pixel(3, 5));
+//! ```
+//!
+//! The emulated panel is mounted so that the orientation the driver sets up for
+//! [`DisplayRotation::Rotate0`](crate::displayrotation::DisplayRotation::Rotate0) reads upright.
+//! Continuous hardware scrolling is not emulated, only whether it is active.
+//!
+//! Only available with the `std` feature.
+
+use std::{fmt, vec, vec::Vec};
+
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError};
+
+use crate::{
+    display::DisplayVariant, displayrotation::DisplayRotation, displays::generic::Controller,
+    screenshot::Frame,
+};
+
+/// Emulated SH110x/SSD130x display controller and panel
+#[derive(Debug, Clone)]
+pub struct Emulator {
+    controller: Controller,
+    width: u8,
+    height: u8,
+    column_offset: u8,
+    large_page_address: bool,
+    ram_columns: u8,
+    ram_pages: u8,
+    gddram: Vec<u8>,
+    page: u8,
+    column: u8,
+    vertical_addressing: bool,
+    segment_remap: bool,
+    reverse_com_dir: bool,
+    start_line: u8,
+    display_offset: u8,
+    contrast: u8,
+    inverted: bool,
+    all_on: bool,
+    display_on: bool,
+    charge_pump: bool,
+    scrolling: bool,
+    transactions: (usize, usize),
+}
+
+impl Emulator {
+    /// Create an emulator for the panel described by `variant`, in the reset state of the
+    /// controller with display RAM cleared.
+    ///
+    /// Display RAM is assumed to be centred on the panel, i.e. `width + 2 * column_offset`
+    /// columns wide, and at least 64 rows high.
+    pub fn new<DV>(variant: &DV) -> Self
+    where
+        DV: DisplayVariant,
+    {
+        Self::with_geometry(
+            variant.controller(),
+            (variant.width(), variant.height()),
+            variant.column_offset(),
+            variant.large_page_address(),
+        )
+    }
+
+    /// Remove the supply of the controller and restore it. Display RAM and all settings return
+    /// to their reset state, the transaction counts are kept.
+    pub fn power_cycle(&mut self) {
+        *self = Emulator {
+            transactions: self.transactions,
+            ..Self::with_geometry(
+                self.controller,
+                (self.width, self.height),
+                self.column_offset,
+                self.large_page_address,
+            )
+        };
+    }
+
+    fn with_geometry(
+        controller: Controller,
+        size: (u8, u8),
+        column_offset: u8,
+        large_page_address: bool,
+    ) -> Self {
+        let (width, height) = size;
+        let ram_columns = width + 2 * column_offset;
+        let ram_pages = height.max(64).div_ceil(8);
+
+        Emulator {
+            controller,
+            width,
+            height,
+            column_offset,
+            large_page_address,
+            ram_columns,
+            ram_pages,
+            gddram: vec![0; ram_columns as usize * ram_pages as usize],
+            page: 0,
+            column: 0,
+            vertical_addressing: false,
+            segment_remap: false,
+            reverse_com_dir: false,
+            start_line: 0,
+            display_offset: 0,
+            contrast: 0x80,
+            inverted: false,
+            all_on: false,
+            display_on: false,
+            charge_pump: false,
+            scrolling: false,
+            transactions: (0, 0),
+        }
+    }
+
+    /// Get the size of the panel in pixels
+    pub fn size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+
+    /// Get whether the pixel at `x`, `y` of the panel is lit, taking into account the RAM
+    /// mapping, start line, invert, entire display on and display on/off state. Pixels outside
+    /// of the panel are never lit.
+    pub fn pixel(&self, x: u8, y: u8) -> bool {
+        if !self.display_on || x >= self.width || y >= self.height {
+            return false;
+        }
+        if self.all_on {
+            return true;
+        }
+
+        let column = if self.segment_remap {
+            self.column_offset + x
+        } else {
+            self.ram_columns - 1 - self.column_offset - x
+        };
+        let line = if self.reverse_com_dir {
+            y
+        } else {
+            self.height - 1 - y
+        };
+        let ram_rows = self.ram_pages as u16 * 8;
+        let row = (line as u16 + self.start_line as u16 + self.display_offset as u16) % ram_rows;
+
+        self.ram_pixel(column, row as u8) ^ self.inverted
+    }
+
+    /// Get the bit at `column`, `row` of display RAM
+    pub fn ram_pixel(&self, column: u8, row: u8) -> bool {
+        let idx = (row / 8) as usize * self.ram_columns as usize + column as usize;
+
+        self.gddram[idx] & (1 << (row % 8)) != 0
+    }
+
+    /// Get the image of the panel as seen by a user who mounted it with `rotation`, i.e. in the
+    /// coordinates `GraphicsMode` uses for that rotation. A display set up with a
+    /// [`Mirror`](crate::displayrotation::Mirror) shows up mirrored, the way the glass looks.
+    pub fn frame(&self, rotation: DisplayRotation) -> Frame {
+        let (w, h) = (self.width as u32, self.height as u32);
+
+        match rotation {
+            DisplayRotation::Rotate0 => Frame::from_fn(w, h, |x, y| self.pixel(x as u8, y as u8)),
+            DisplayRotation::Rotate90 => {
+                Frame::from_fn(h, w, |x, y| self.pixel((w - 1 - y) as u8, x as u8))
+            }
+            DisplayRotation::Rotate180 => Frame::from_fn(w, h, |x, y| {
+                self.pixel((w - 1 - x) as u8, (h - 1 - y) as u8)
+            }),
+            DisplayRotation::Rotate270 => {
+                Frame::from_fn(h, w, |x, y| self.pixel(y as u8, (h - 1 - x) as u8))
+            }
+        }
+    }
+
+    /// Get the contents of display RAM as an image, one column per pixel
+    pub fn gddram_frame(&self) -> Frame {
+        Frame::from_fn(
+            self.ram_columns as u32,
+            self.ram_pages as u32 * 8,
+            |x, y| self.ram_pixel(x as u8, y as u8),
+        )
+    }
+
+    /// Get the display RAM, one page of `width + 2 * column_offset` bytes after the other
+    pub fn gddram(&self) -> &[u8] {
+        &self.gddram
+    }
+
+    /// Get the page and column address the next data byte is written to
+    pub fn address(&self) -> (u8, u8) {
+        (self.page, self.column)
+    }
+
+    /// Get whether vertical addressing mode is selected
+    pub fn vertical_addressing(&self) -> bool {
+        self.vertical_addressing
+    }
+
+    /// Get whether the segment remap is set, i.e. columns are mapped from the right
+    pub fn segment_remap(&self) -> bool {
+        self.segment_remap
+    }
+
+    /// Get whether the COM scan direction is reversed
+    pub fn reverse_com_dir(&self) -> bool {
+        self.reverse_com_dir
+    }
+
+    /// Get the display start line
+    pub fn start_line(&self) -> u8 {
+        self.start_line
+    }
+
+    /// Get the vertical shift set with the display offset command
+    pub fn display_offset(&self) -> u8 {
+        self.display_offset
+    }
+
+    /// Get the contrast
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    /// Get whether the display is inverted
+    pub fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Get whether the entire display is forced on
+    pub fn all_on(&self) -> bool {
+        self.all_on
+    }
+
+    /// Get whether the display is on
+    pub fn display_on(&self) -> bool {
+        self.display_on
+    }
+
+    /// Get whether the charge pump or DC-DC converter is enabled
+    pub fn charge_pump(&self) -> bool {
+        self.charge_pump
+    }
+
+    /// Get whether a continuous hardware scroll is active
+    pub fn scrolling(&self) -> bool {
+        self.scrolling
+    }
+
+    /// Get the number of command and data transactions received, to check bus overhead
+    pub fn transactions(&self) -> (usize, usize) {
+        self.transactions
+    }
+
+    /// Decode a batch of commands, which have to be complete
+    fn commands(&mut self, bytes: &[u8]) -> Result<(), DisplayError> {
+        let mut bytes = bytes;
+        // scroll setup takes a column range as well on the SSD1309
+        let ssd1309 = self.controller == Controller::Ssd1309;
+
+        while let Some(&opcode) = bytes.first() {
+            let len = match opcode {
+                0xB0 if self.large_page_address => 2,
+                0x81 | 0x8D | 0xA8 | 0xA9 | 0xAD | 0xD3 | 0xD5 | 0xD9 | 0xDA | 0xDB | 0xDC => 2,
+                0xA3 => 3,
+                0x26 | 0x27 | 0x29 | 0x2A if ssd1309 => 8,
+                0x29 | 0x2A => 6,
+                0x26 | 0x27 => 7,
+                _ => 1,
+            };
+            if bytes.len() < len {
+                return Err(DisplayError::InvalidFormatError);
+            }
+            let arg = bytes.get(1).copied().unwrap_or(0);
+
+            match opcode {
+                0x00..=0x0F => self.column = (self.column & 0xF0) | opcode,
+                0x10..=0x1F => self.column = (self.column & 0x0F) | ((opcode & 0xF) << 4),
+                0x20 | 0x21 => self.vertical_addressing = opcode & 1 != 0,
+                0x2E | 0x2F => self.scrolling = opcode & 1 != 0,
+                0x40..=0x7F => self.start_line = opcode & 0x3F,
+                0x81 => self.contrast = arg,
+                0xA0 | 0xA1 => self.segment_remap = opcode & 1 != 0,
+                0xA4 | 0xA5 => self.all_on = opcode & 1 != 0,
+                0xA6 | 0xA7 => self.inverted = opcode & 1 != 0,
+                0xAE | 0xAF => self.display_on = opcode & 1 != 0,
+                0xB0 if self.large_page_address => self.page = arg,
+                0xB0..=0xBF => self.page = opcode & 0xF,
+                0xC0..=0xCF => self.reverse_com_dir = opcode & 0x8 != 0,
+                0x8D => self.charge_pump = arg & 0x04 != 0,
+                0xAD => self.charge_pump = arg & 0x01 != 0,
+                0xD3 => self.display_offset = arg,
+                0xDC => self.start_line = arg,
+                // scroll setup, clock, multiplex and other analog settings don't change the image
+                0x26 | 0x27 | 0x29 | 0x2A | 0xA3 | 0xA8 | 0xA9 | 0xD5 | 0xD9 | 0xDA | 0xDB
+                | 0xE3 => {}
+                _ => return Err(DisplayError::InvalidFormatError),
+            }
+
+            bytes = &bytes[len..];
+        }
+
+        Ok(())
+    }
+
+    /// Write a data byte to display RAM and advance the address
+    fn data(&mut self, byte: u8) {
+        let page = self.page % self.ram_pages;
+        let column = self.column % self.ram_columns;
+        self.gddram[page as usize * self.ram_columns as usize + column as usize] = byte;
+
+        if self.vertical_addressing {
+            self.page = (page + 1) % self.ram_pages;
+            if self.page == 0 {
+                self.column = (column + 1) % self.ram_columns;
+            }
+        } else {
+            self.column = (column + 1) % self.ram_columns;
+        }
+    }
+
+    pub(crate) fn send_commands_sync(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.transactions.0 += 1;
+        match cmd {
+            DataFormat::U8(bytes) => self.commands(bytes),
+            DataFormat::U8Iter(iter) => self.commands(&iter.collect::<Vec<_>>()),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    pub(crate) fn send_data_sync(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.transactions.1 += 1;
+        match buf {
+            DataFormat::U8(bytes) => bytes.iter().for_each(|&byte| self.data(byte)),
+            DataFormat::U8Iter(iter) => iter.for_each(|byte| self.data(byte)),
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the panel with one line per row, `#` for lit and `.` for dark pixels
+impl fmt::Display for Emulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                f.write_str(if self.pixel(x, y) { "#" } else { "." })?;
+            }
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl AsyncWriteOnlyDataCommand for Emulator {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_commands_sync(cmd)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_data_sync(buf)
+    }
+}
+
+/// Lets the emulator be inspected while a display mode holds on to it
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl AsyncWriteOnlyDataCommand for &mut Emulator {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_commands_sync(cmd)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_data_sync(buf)
+    }
+}
+
+/// Run a driver call to completion, the emulator never has to wait
+#[cfg(all(test, not(feature = "blocking")))]
+pub(crate) fn block_on<F: core::future::Future>(fut: F) -> F::Output {
+    use std::{
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut fut = core::pin::pin!(fut);
+    match fut.as_mut().poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("emulator returned pending"),
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+pub(crate) fn block_on<T>(output: T) -> T {
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "blocking"))]
+    use display_interface::AsyncWriteOnlyDataCommand;
+    use display_interface::{DataFormat, DisplayError};
+    #[cfg(feature = "blocking")]
+    use display_interface::WriteOnlyDataCommand;
+
+    use core::convert::identity;
+    use std::{cell::RefCell, string::ToString, vec::Vec};
+
+    use super::{block_on, Emulator};
+    use crate::{
+        displays::{sh1106::Sh1106_128_64, ssd1306::Ssd1306_128_64},
+        mode::RawMode,
+        power::NoDelay,
+        prelude::*,
+        test_helpers::setup,
+        Builder,
+    };
+
+    /// Interface to an emulator that also logs the command bytes it receives
+    struct Tap<'a>(&'a mut Emulator, &'a RefCell<Vec<u8>>);
+
+    #[maybe_async_cfg::maybe(
+        sync(
+            feature = "blocking",
+            keep_self,
+            idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+        ),
+        async(not(feature = "blocking"), keep_self)
+    )]
+    impl AsyncWriteOnlyDataCommand for Tap<'_> {
+        async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            if let DataFormat::U8(bytes) = cmd {
+                self.1.borrow_mut().extend_from_slice(bytes);
+            }
+            self.0.send_commands_sync(cmd)
+        }
+
+        async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            self.0.send_data_sync(buf)
+        }
+    }
+
+    #[test]
+    fn init_turns_display_on_blank() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        assert!(emulator.display_on());
+        assert!(emulator.segment_remap());
+        assert!(emulator.reverse_com_dir());
+        assert_eq!(emulator.contrast(), 0x80);
+        assert!(!emulator.to_string().contains('#'));
+    }
+
+    #[test]
+    fn flush_writes_pixels_at_column_offset() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Sh1106_128_64 {}, identity);
+
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(127, 63, 1);
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        assert!(emulator.pixel(0, 0));
+        assert!(emulator.pixel(127, 63));
+        assert!(!emulator.pixel(1, 0));
+        assert!(emulator.ram_pixel(2, 0));
+        assert!(emulator.ram_pixel(129, 63));
+    }
+
+    #[test]
+    fn rotate180_flips_both_axes() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, |builder| {
+            builder.with_rotation(DisplayRotation::Rotate180)
+        });
+
+        display.set_pixel(0, 0, 1);
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        assert!(emulator.pixel(127, 63));
+        assert!(!emulator.pixel(0, 0));
+    }
+
+    #[test]
+    fn scroll_offset_keeps_origin_in_place() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        block_on(display.set_scroll_offset(12)).unwrap();
+        display.set_pixel(5, 0, 1);
+        display.set_pixel(5, 63, 1);
+        block_on(display.flush()).unwrap();
+
+        let emulator = emulator.borrow();
+        assert_eq!(emulator.start_line(), 12);
+        assert!(emulator.pixel(5, 0));
+        assert!(emulator.pixel(5, 63));
+        assert!(emulator.ram_pixel(5, 12));
+    }
+
+    #[test]
+    fn short_display_shifts_within_display_ram() {
+        use crate::{burnin::BurnInConfig, displays::ssd1306::Ssd1306_128_32};
+
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_32 {}, identity);
+        // fill display RAM, rows below the display show up unless they are blanked
+        for page in 0..8 {
+            let mut emulator = emulator.borrow_mut();
+            emulator
+                .send_commands_sync(DataFormat::U8(&[0xB0 | page, 0x00, 0x10]))
+                .unwrap();
+            emulator.send_data_sync(DataFormat::U8(&[0xFF; 128])).unwrap();
+        }
+        let shown = |x, y| emulator.borrow().pixel(x, y);
+
+        // 32 of the 64 rows of display RAM can't hold the ring buffer
+        let scrolled = block_on(display.set_scroll_offset(3));
+        assert!(matches!(scrolled, Err(DisplayError::OutOfBoundsError)));
+        block_on(display.set_scroll_offset(32)).unwrap();
+
+        display.clear();
+        display.set_pixel(6, 31, 1);
+        block_on(display.flush()).unwrap();
+        let config = BurnInConfig::new().with_pixel_shift(100, 1);
+        block_on(display.set_burn_in(Some(config), 0, &mut NoDelay)).unwrap();
+        block_on(display.tick(100, &mut NoDelay)).unwrap();
+        block_on(display.tick(200, &mut NoDelay)).unwrap();
+
+        // moved up into a blanked row of display RAM, instead of wrapping around
+        assert_eq!(emulator.borrow().start_line(), 1);
+        assert!(shown(7, 30));
+        assert!((0..128).all(|x| !shown(x, 31)));
+    }
+
+    #[test]
+    fn runtime_variant_uses_runtime_geometry() {
+        use crate::displays::generic::{Controller, RuntimeVariant};
+
+        // smaller than the 160x160 consts of `RuntimeVariant`, in 132 columns of display RAM
+        let variant = RuntimeVariant::new(Controller::Sh1106, 128, 64).with_column_offset(2);
+        let mut emulator = Emulator::new(&variant);
+        assert_eq!(emulator.size(), (128, 64));
+        let log = RefCell::new(Vec::new());
+        let raw = Builder::new(variant).connect(Tap(&mut emulator, &log));
+        let mut display: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+        assert_eq!(display.get_dimensions(), (128, 64));
+
+        for (x, y) in [(0, 0), (127, 0), (0, 63), (127, 63)] {
+            display.set_pixel(x, y, 1);
+        }
+        block_on(display.flush()).unwrap();
+
+        for (x, y) in [(0, 0), (127, 0), (0, 63), (127, 63)] {
+            assert!(emulator.pixel(x, y));
+        }
+        for (column, row) in [(2, 0), (129, 0), (2, 63), (129, 63)] {
+            assert!(emulator.ram_pixel(column, row));
+        }
+        let lit: u32 = emulator.gddram().iter().map(|byte| byte.count_ones()).sum();
+        assert_eq!(lit, 4);
+        assert!(log.borrow().windows(4).any(|bytes| bytes == [0xD3, 0, 0xDA, 0x12]));
+
+        // display offset and COM pins are taken from the variant, not the controller family
+        let variant = RuntimeVariant::new(Controller::Sh1106, 128, 64)
+            .with_display_offset(4)
+            .with_alternative_com_pins(false);
+        let mut emulator = Emulator::new(&variant);
+        log.borrow_mut().clear();
+        let raw = Builder::new(variant).connect(Tap(&mut emulator, &log));
+        let _: RawMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+
+        assert_eq!(emulator.display_offset(), 4);
+        assert!(log.borrow().windows(4).any(|bytes| bytes == [0xD3, 4, 0xDA, 0x02]));
+    }
+
+    #[test]
+    fn hardware_scroll_setup_keeps_the_image() {
+        use crate::{
+            displays::generic::{Controller, RuntimeVariant},
+            scroll::{HScrollDir, NFrames, ScrollConfig, VHScrollDir},
+        };
+
+        for controller in [Controller::Ssd1306, Controller::Ssd1309] {
+            let variant = RuntimeVariant::new(controller, 128, 64);
+            let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(variant, identity);
+            display.set_pixel(5, 9, 1);
+            block_on(display.flush()).unwrap();
+
+            block_on(display.start_scroll(ScrollConfig::Horizontal {
+                direction: HScrollDir::RightToLeft,
+                start_page: 0,
+                end_page: 7,
+                interval: NFrames::F2,
+            }))
+            .unwrap();
+            block_on(display.start_scroll(ScrollConfig::Diagonal {
+                direction: VHScrollDir::VerticalRight,
+                start_page: 0,
+                end_page: 7,
+                interval: NFrames::F2,
+                vertical_offset: 1,
+                fixed_rows: 0,
+                scroll_rows: 64,
+            }))
+            .unwrap();
+
+            // the column range sent to the SSD1309 must not be taken for commands that move the
+            // image
+            let emulator = emulator.borrow();
+            assert!(emulator.scrolling());
+            assert!(emulator.pixel(5, 9));
+            assert_eq!(emulator.to_string().matches('#').count(), 1);
+        }
+    }
+
+    #[test]
+    fn frame_matches_buffer_in_all_rotations() {
+        for rotation in [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ] {
+            let (mut display, emulator): (GraphicsMode<_, _>, _) =
+                setup(Sh1106_128_64 {}, |builder| builder.with_rotation(rotation));
+
+            // asymmetric, so that flips show up
+            for i in 0..20 {
+                display.set_pixel(i, 0, 1);
+                display.set_pixel(0, i / 2, 1);
+            }
+            block_on(display.flush()).unwrap();
+            let frame = display.frame();
+
+            assert_eq!(frame.diff(&emulator.borrow().frame(rotation)), []);
+        }
+    }
+
+    /// An `L` with a dot to its right, which looks different after every rotation and mirror
+    fn pattern(x: u32, y: u32) -> bool {
+        (x < 3 && y < 40) || (x < 20 && (37..40).contains(&y)) || (x == 30 && y == 5)
+    }
+
+    /// Maps a pixel drawn at `x`, `y` to the panel
+    type ToGlass = fn(u32, u32) -> (u8, u8);
+
+    /// Glass coordinates of a pixel drawn at `x`, `y`, rotated clockwise by 90 and 270 degrees
+    const QUARTER_TURNS: [(DisplayRotation, ToGlass); 2] = [
+        (DisplayRotation::Rotate90, |x, y| (127 - y as u8, x as u8)),
+        (DisplayRotation::Rotate270, |x, y| (y as u8, 63 - x as u8)),
+    ];
+
+    #[test]
+    fn quarter_turns_rotate_instead_of_transposing() {
+        for (rotation, to_glass) in QUARTER_TURNS {
+            let (mut display, emulator): (GraphicsMode<_, _>, _) =
+                setup(Ssd1306_128_64 {}, |builder| builder.with_rotation(rotation));
+
+            assert_eq!(display.get_dimensions(), (64, 128));
+            for y in 0..128 {
+                for x in 0..64 {
+                    display.set_pixel(x, y, pattern(x, y) as u8);
+                }
+            }
+            block_on(display.flush()).unwrap();
+
+            let emulator = emulator.borrow();
+            for y in 0..128 {
+                for x in 0..64 {
+                    let (gx, gy) = to_glass(x, y);
+                    assert_eq!(emulator.pixel(gx, gy), pattern(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotations_send_remap_commands() {
+        for (rotation, remap) in [
+            (DisplayRotation::Rotate0, [0xA1, 0xC8]),
+            (DisplayRotation::Rotate90, [0xA0, 0xC8]),
+            (DisplayRotation::Rotate180, [0xA0, 0xC0]),
+            (DisplayRotation::Rotate270, [0xA1, 0xC0]),
+        ] {
+            let variant = Ssd1306_128_64 {};
+            let mut emulator = Emulator::new(&variant);
+            let log = RefCell::new(Vec::new());
+            let raw = Builder::new(variant).connect(Tap(&mut emulator, &log));
+            let mut display: GraphicsMode<_, _> = block_on(raw.init_without_reset()).unwrap();
+            log.borrow_mut().clear();
+
+            block_on(display.set_rotation(rotation)).unwrap();
+            assert_eq!(*log.borrow(), remap);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quarter_turns_rotate_filled_rectangles() {
+        use embedded_graphics_core::{
+            draw_target::DrawTarget,
+            geometry::{Point, Size},
+            pixelcolor::BinaryColor,
+            primitives::Rectangle,
+        };
+
+        for (rotation, to_glass) in QUARTER_TURNS {
+            let (mut display, emulator): (GraphicsMode<_, _>, _) =
+                setup(Ssd1306_128_64 {}, |builder| builder.with_rotation(rotation));
+
+            for (x, y, w, h) in [(0, 0, 3, 40), (0, 37, 20, 3), (30, 5, 1, 1)] {
+                let area = Rectangle::new(Point::new(x, y), Size::new(w, h));
+                display.fill_solid(&area, BinaryColor::On).unwrap();
+            }
+            block_on(display.flush()).unwrap();
+
+            let emulator = emulator.borrow();
+            for y in 0..128 {
+                for x in 0..64 {
+                    let (gx, gy) = to_glass(x, y);
+                    assert_eq!(emulator.pixel(gx, gy), pattern(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mirror_combines_with_rotation() {
+        let rotations: [(DisplayRotation, ToGlass); 4] = [
+            (DisplayRotation::Rotate0, |x, y| (x as u8, y as u8)),
+            QUARTER_TURNS[0],
+            (DisplayRotation::Rotate180, |x, y| (127 - x as u8, 63 - y as u8)),
+            QUARTER_TURNS[1],
+        ];
+
+        for (rotation, to_glass) in rotations {
+            for mirror in [Mirror::None, Mirror::Horizontal, Mirror::Vertical, Mirror::Both] {
+                let (mut display, emulator): (GraphicsMode<_, _>, _) =
+                    setup(Sh1106_128_64 {}, |builder| {
+                        builder.with_rotation(rotation).with_mirror(mirror)
+                    });
+
+                let (width, height) = display.get_dimensions();
+                let (width, height) = (width as u32, height as u32);
+                for y in 0..height {
+                    for x in 0..width {
+                        display.set_pixel(x, y, pattern(x, y) as u8);
+                    }
+                }
+                block_on(display.flush()).unwrap();
+
+                let emulator = emulator.borrow();
+                let (flip_x, flip_y) = mirror.flips();
+                for y in 0..height {
+                    for x in 0..width {
+                        let mx = if flip_x { width - 1 - x } else { x };
+                        let my = if flip_y { height - 1 - y } else { y };
+                        let (gx, gy) = to_glass(mx, my);
+                        assert_eq!(emulator.pixel(gx, gy), pattern(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_mirror_at_runtime() {
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+
+        display.set_pixel(0, 0, 1);
+        block_on(display.flush()).unwrap();
+
+        block_on(display.set_mirror(Mirror::Horizontal)).unwrap();
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(display.get_mirror(), Mirror::Horizontal);
+        let emulator = emulator.borrow();
+        assert!(!emulator.segment_remap());
+        assert!(emulator.pixel(127, 0));
+        assert!(!emulator.pixel(0, 0));
+    }
+
+    #[test]
+    fn invert_and_display_off() {
+        let (_, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let mut emulator = emulator.borrow_mut();
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xA7]))).unwrap();
+        assert!(emulator.pixel(10, 10));
+
+        block_on(emulator.send_commands(DataFormat::U8(&[0xAE]))).unwrap();
+        assert!(!emulator.pixel(10, 10));
+    }
+
+    #[test]
+    fn init_resets_then_initialises() {
+        #[cfg(feature = "blocking")]
+        use hal::delay::DelayNs;
+        use hal::digital::{ErrorType, OutputPin};
+        #[cfg(not(feature = "blocking"))]
+        use embedded_hal_async::delay::DelayNs;
+
+        /// Reset pin and delay logging the reset sequence
+        #[derive(Default)]
+        struct Log(Vec<&'static str>);
+
+        impl ErrorType for Log {
+            type Error = core::convert::Infallible;
+        }
+
+        impl OutputPin for Log {
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                self.0.push("low");
+                Ok(())
+            }
+
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                self.0.push("high");
+                Ok(())
+            }
+        }
+
+        #[maybe_async_cfg::maybe(
+            sync(feature = "blocking", keep_self),
+            async(not(feature = "blocking"), keep_self)
+        )]
+        impl DelayNs for Log {
+            async fn delay_ns(&mut self, _ns: u32) {
+                self.0.push("delay");
+            }
+        }
+
+        let variant = Ssd1306_128_64 {};
+        let mut emulator = Emulator::new(&variant);
+        let (mut reset, mut delay) = (Log::default(), Log::default());
+        let uninit = Builder::new(variant).connect(&mut emulator);
+        let mut display: GraphicsMode<_, _> =
+            block_on(uninit.init(&mut reset, &mut delay)).unwrap();
+
+        display.set_pixel(1, 2, 1);
+        block_on(display.flush()).unwrap();
+
+        assert_eq!(reset.0, ["high", "low", "high"]);
+        assert!(!delay.0.is_empty());
+        assert!(emulator.display_on() && emulator.pixel(1, 2));
+    }
+
+    #[test]
+    fn burn_in_protection_shifts_dims_and_sleeps() {
+        use crate::burnin::{BurnInConfig, IdleState, Screensaver};
+
+        let (mut display, emulator): (GraphicsMode<_, _>, _) = setup(Ssd1306_128_64 {}, identity);
+        let shown = |x, y| emulator.borrow().pixel(x, y);
+
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(127, 40, 1);
+        display.set_pixel(0, 5, 1);
+        block_on(display.flush()).unwrap();
+        let config = BurnInConfig::new()
+            .with_pixel_shift(100, 2)
+            .with_dimming(1000, 0x10)
+            .with_screensaver(5000, Screensaver::Sleep);
+        block_on(display.set_burn_in(Some(config), 0, &mut NoDelay)).unwrap();
+
+        assert_eq!(block_on(display.tick(50, &mut NoDelay)).unwrap(), IdleState::Active);
+        assert!(shown(10, 20));
+        block_on(display.tick(100, &mut NoDelay)).unwrap();
+        let sent = emulator.borrow().transactions().1;
+        block_on(display.tick(200, &mut NoDelay)).unwrap();
+        // moved right, past the right edge, blanking the columns left behind
+        assert!(shown(12, 20) && !shown(10, 20) && !shown(11, 20));
+        assert!((0..128).all(|x| !shown(x, 40)));
+        assert!(shown(2, 5) && !shown(0, 5) && !shown(1, 5));
+        // only the columns that changed are sent, blanks separately
+        assert_eq!(emulator.borrow().transactions().1 - sent, 3);
+        block_on(display.tick(300, &mut NoDelay)).unwrap();
+        // and up
+        assert!(shown(12, 19));
+
+        assert_eq!(block_on(display.tick(1000, &mut NoDelay)).unwrap(), IdleState::Dimmed);
+        assert_eq!(emulator.borrow().contrast(), 0x10);
+        block_on(display.set_contrast(0x50)).unwrap();
+        assert_eq!(emulator.borrow().contrast(), 0x10);
+        block_on(display.activity(1100, &mut NoDelay)).unwrap();
+        assert_eq!(emulator.borrow().contrast(), 0x50);
+
+        assert_eq!(block_on(display.tick(6100, &mut NoDelay)).unwrap(), IdleState::Screensaver);
+        assert!(!emulator.borrow().display_on() && !emulator.borrow().charge_pump());
+        block_on(display.activity(6200, &mut NoDelay)).unwrap();
+        // shifted back by a column while dimmed, and kept while asleep
+        assert!(shown(11, 19) && emulator.borrow().charge_pump());
+
+        block_on(display.set_burn_in(None, 6300, &mut NoDelay)).unwrap();
+        assert!(shown(10, 20) && shown(127, 40));
+        assert_eq!(emulator.borrow().start_line(), 0);
+    }
+
+    #[test]
+    fn rejects_truncated_and_unknown_commands() {
+        let mut emulator = Emulator::new(&Ssd1306_128_64 {});
+
+        assert!(block_on(emulator.send_commands(DataFormat::U8(&[0x81]))).is_err());
+        assert!(block_on(emulator.send_commands(DataFormat::U8(&[0xFF]))).is_err());
+        let scroll = [0x27, 0x00, 0, 0b111, 7, 0x00, 0xFF];
+        assert!(block_on(emulator.send_commands(DataFormat::U8(&scroll[..6]))).is_err());
+    }
+
+    #[test]
+    fn decodes_commands_batched_after_scroll_setup() {
+        use crate::displays::generic::{Controller, RuntimeVariant};
+
+        let mut emulator = Emulator::new(&Ssd1306_128_64 {});
+        let batch = [0x27, 0x00, 0, 0b111, 7, 0x00, 0xFF, 0x2A, 0x00, 0, 0b111, 7, 1, 0xAF];
+        block_on(emulator.send_commands(DataFormat::U8(&batch))).unwrap();
+        assert!(emulator.display_on());
+
+        let variant = RuntimeVariant::new(Controller::Ssd1309, 128, 64);
+        let mut emulator = Emulator::new(&variant);
+        let batch = [0x26, 0x00, 0, 0b111, 7, 0x00, 0, 127, 0x81, 0x42];
+        block_on(emulator.send_commands(DataFormat::U8(&batch))).unwrap();
+        assert_eq!(emulator.contrast(), 0x42);
+    }
+}
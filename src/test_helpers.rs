@@ -1,57 +1,54 @@
-//! Helpers for use in examples and tests
-
-use embedded_hal::{
-    blocking::{
-        i2c,
-        spi::{self, Transfer},
-    },
-    digital::v2::OutputPin,
-};
-
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct SpiStub;
+//! Helpers for tests
 
-impl spi::Write<u8> for SpiStub {
-    type Error = ();
+use std::{cell::RefCell, rc::Rc};
 
-    fn write(&mut self, _buf: &[u8]) -> Result<(), ()> {
-        Ok(())
-    }
-}
+#[cfg(not(feature = "blocking"))]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError};
+#[cfg(feature = "blocking")]
+use display_interface::WriteOnlyDataCommand;
 
-impl Transfer<u8> for SpiStub {
-    type Error = ();
-
-    fn transfer<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], ()> {
-        Ok(buf)
-    }
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct PinStub;
-
-impl OutputPin for PinStub {
-    type Error = ();
+use crate::{
+    display::DisplayVariant,
+    mode::displaymode::DisplayModeTrait,
+    sim::{block_on, Emulator},
+    Builder,
+};
 
-    fn set_high(&mut self) -> Result<(), ()> {
-        Ok(())
+/// Interface to an emulator that can be inspected and power cycled while a display holds it
+pub(crate) struct Shared(pub(crate) Rc<RefCell<Emulator>>);
+
+#[maybe_async_cfg::maybe(
+    sync(
+        feature = "blocking",
+        keep_self,
+        idents(AsyncWriteOnlyDataCommand(sync = "WriteOnlyDataCommand"),)
+    ),
+    async(not(feature = "blocking"), keep_self)
+)]
+impl AsyncWriteOnlyDataCommand for Shared {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.borrow_mut().send_commands_sync(cmd)
     }
 
-    fn set_low(&mut self) -> Result<(), ()> {
-        Ok(())
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.borrow_mut().send_data_sync(buf)
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct I2cStub;
-
-impl i2c::Write for I2cStub {
-    type Error = ();
-
-    fn write(&mut self, _addr: u8, _buf: &[u8]) -> Result<(), ()> {
-        Ok(())
-    }
+/// Initialise a display of `variant` in `MODE` on an emulator, without a reset, and return it
+/// with the emulator. `configure` sets up the builder, pass [`core::convert::identity`] to keep
+/// the defaults.
+pub(crate) fn setup<DV, MODE>(
+    variant: DV,
+    configure: impl FnOnce(Builder<DV>) -> Builder<DV>,
+) -> (MODE, Rc<RefCell<Emulator>>)
+where
+    DV: DisplayVariant,
+    MODE: DisplayModeTrait<DV, Shared>,
+{
+    let emulator = Rc::new(RefCell::new(Emulator::new(&variant)));
+    let uninit = configure(Builder::new(variant)).connect(Shared(emulator.clone()));
+
+    (block_on(uninit.init_without_reset()).unwrap(), emulator)
 }